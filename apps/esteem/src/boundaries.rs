@@ -0,0 +1,80 @@
+use super::{config::DepConstraint, graph::NxProject, workspace::EsteemWorkspace};
+use std::slice::from_ref;
+
+/// whether `value` matches `pattern`, where `*` matches anything and a trailing `*`
+/// matches any suffix
+pub(crate) fn matches_pattern(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => pattern == value,
+    }
+}
+
+fn has_any_tag(tags: &[String], patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| tags.iter().any(|tag| matches_pattern(pattern, tag)))
+}
+
+/// checks every project of the workspace against the constraints and returns a
+/// description of each violation
+pub fn check_boundaries(
+    constraints: &[DepConstraint],
+    graph: &NxProject,
+    workspace: &EsteemWorkspace,
+) -> Vec<String> {
+    let mut violations = vec![];
+    let edges = graph.get_edges();
+    for project in workspace.all_projects_rep.iter() {
+        let tags = project.get_tags();
+        let applicable = constraints.iter().filter(|c| {
+            c.source_tag == "*" || has_any_tag(&tags, from_ref(&c.source_tag))
+        });
+        for constraint in applicable {
+            let targets = edges
+                .iter()
+                .filter(|(source, _)| source == &project.name)
+                .map(|(_, target)| target);
+            for target in targets {
+                let target_tags = match workspace.get_project(target.to_owned()) {
+                    Ok(p) => p.get_tags(),
+                    Err(_) => continue,
+                };
+                if let Some(allowed) = &constraint.only_depend_on_libs_with_tags {
+                    if !has_any_tag(&target_tags, allowed) {
+                        violations.push(format!(
+                            "{:?} ({:?}) may only depend on projects tagged with {:?}, but depends on {:?} ({:?})",
+                            project.name, constraint.source_tag, allowed, target, target_tags
+                        ));
+                    }
+                }
+                if has_any_tag(&target_tags, &constraint.not_depend_on_libs_with_tags) {
+                    violations.push(format!(
+                        "{:?} ({:?}) may not depend on projects tagged with {:?}, but depends on {:?} ({:?})",
+                        project.name,
+                        constraint.source_tag,
+                        constraint.not_depend_on_libs_with_tags,
+                        target,
+                        target_tags
+                    ));
+                }
+            }
+            for package in workspace
+                .resolve(&project.dependencies)
+                .get_all_dependencies()
+            {
+                if constraint
+                    .banned_packages
+                    .iter()
+                    .any(|pattern| matches_pattern(pattern, &package))
+                {
+                    violations.push(format!(
+                        "{:?} ({:?}) may not declare the package {:?}",
+                        project.name, constraint.source_tag, package
+                    ));
+                }
+            }
+        }
+    }
+    violations
+}
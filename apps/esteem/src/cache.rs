@@ -0,0 +1,189 @@
+use super::{
+    constants::{
+        CACHE_DIRECTORY, NODE_MODULES_DIRECTORY, PROJECT_FILE, WORKSPACE_ROOT_FILES,
+    },
+    graph::NxProject,
+    workspace::EsteemWorkspace,
+    LibraryError,
+};
+use duct::cmd;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeSet,
+    env::current_dir,
+    ffi::OsString,
+    fs::{create_dir_all, read, read_dir, read_to_string, remove_file, write},
+    path::{Component, Path, PathBuf},
+};
+
+/// Stores the complete project graph in the cache directory, keyed by the contents of
+/// the files that NX uses to calculate it
+#[derive(Debug)]
+pub struct GraphCache {
+    directory: PathBuf,
+    key: String,
+}
+
+impl GraphCache {
+    pub fn new(workspace: &EsteemWorkspace) -> Self {
+        let root = current_dir().unwrap();
+        // the hash has to be stable across builds, so `DefaultHasher` can not be used
+        let mut hasher = Sha256::new();
+        hash_bytes(env!("CARGO_PKG_VERSION").as_bytes(), &mut hasher);
+        // paths are relative to the workspace root so that moving it keeps the cache valid
+        WORKSPACE_ROOT_FILES
+            .iter()
+            .for_each(|file| hash_file(Path::new(file), &mut hasher));
+        get_project_files(workspace)
+            .iter()
+            .for_each(|file| hash_file(file, &mut hasher));
+        Self {
+            directory: root.join(CACHE_DIRECTORY),
+            key: hasher.finalize()[..8]
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        self.directory.join(format!("graph-{}.json", self.key))
+    }
+
+    pub fn read(&self) -> Option<NxProject> {
+        let data = read_to_string(self.path()).ok()?;
+        match serde_json::from_str(&data) {
+            Ok(graph) => {
+                info!("Using cached project graph {:?}", self.path());
+                Some(graph)
+            }
+            Err(_) => {
+                warn!("Ignoring corrupt cached project graph {:?}", self.path());
+                None
+            }
+        }
+    }
+
+    /// writes the graph to the cache and removes the graphs cached for older keys
+    pub fn write(&self, graph: &NxProject) -> Result<(), LibraryError> {
+        create_dir_all(&self.directory).map_err(|err| {
+            LibraryError(format!("Could not create {:?}: {err}", self.directory))
+        })?;
+        for entry in read_dir(&self.directory).unwrap().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("graph-") && name.ends_with(".json") {
+                trace!("Removing stale cached project graph {name:?}");
+                remove_file(entry.path()).ok();
+            }
+        }
+        info!("Caching project graph to {:?}", self.path());
+        write(self.path(), serde_json::to_string(graph).unwrap()).map_err(|err| {
+            LibraryError(format!("Could not write {:?}: {err}", self.path()))
+        })
+    }
+}
+
+/// hashes `bytes` prefixed with their length, so that consecutive values can not be
+/// confused with each other
+fn hash_bytes(bytes: &[u8], hasher: &mut Sha256) {
+    hasher.update((bytes.len() as u64).to_le_bytes());
+    hasher.update(bytes);
+}
+
+fn hash_file(path: &Path, hasher: &mut Sha256) {
+    hash_bytes(path.to_string_lossy().as_bytes(), hasher);
+    hash_bytes(&read(path).unwrap_or_default(), hasher);
+}
+
+/// returns the files of the projects that NX reads to calculate the project graph: the
+/// ones that git does not ignore, except the build outputs of the projects. A project at
+/// the root of the workspace only has its `project.json` and its `sourceRoot`, since its
+/// directory is the whole workspace.
+fn get_project_files(workspace: &EsteemWorkspace) -> BTreeSet<PathBuf> {
+    let mut files = BTreeSet::new();
+    let mut directories = vec![];
+    let mut outputs = vec![];
+    for project in &workspace.all_projects_rep {
+        // paths can be written as `./dist/...`, the listed files never start with `./`
+        outputs.extend(project.get_output_path().map(|output| {
+            output
+                .strip_prefix(".")
+                .map(Path::to_path_buf)
+                .unwrap_or(output)
+        }));
+        let root = &workspace.projects[&project.name];
+        match root.components().all(|c| c == Component::CurDir) {
+            true => {
+                files.insert(PathBuf::from(PROJECT_FILE));
+                directories.extend(project.get_source_root());
+            }
+            false => directories.push(root.to_owned()),
+        }
+    }
+    match list_git_files(&directories) {
+        Some(listed) => files.extend(listed),
+        None => directories
+            .iter()
+            .for_each(|directory| list_files(directory, &mut files)),
+    }
+    files.retain(|file| !outputs.iter().any(|output| file.starts_with(output)));
+    files
+}
+
+/// lists the files in `directories` that git does not ignore, `None` if the workspace is
+/// not a git repository
+fn list_git_files(directories: &[PathBuf]) -> Option<BTreeSet<PathBuf>> {
+    if directories.is_empty() {
+        return Some(BTreeSet::new());
+    }
+    let args = [
+        "ls-files",
+        "-z",
+        "--cached",
+        "--others",
+        "--exclude-standard",
+        "--",
+    ]
+    .into_iter()
+    .map(OsString::from)
+    .chain(directories.iter().map(|d| d.as_os_str().to_owned()));
+    let output = cmd("git", args)
+        .stdout_capture()
+        .stderr_null()
+        .unchecked()
+        .run()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let files = output
+        .stdout
+        .split(|byte| *byte == 0)
+        .filter(|file| !file.is_empty())
+        .map(|file| PathBuf::from(String::from_utf8_lossy(file).into_owned()))
+        .collect();
+    Some(files)
+}
+
+/// lists every file in a directory recursively, skipping `node_modules` and hidden
+/// entries
+fn list_files(path: &Path, files: &mut BTreeSet<PathBuf>) {
+    let entries = match read_dir(path) {
+        Ok(entries) => entries.flatten(),
+        Err(_) => return,
+    };
+    for entry in entries {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || name == NODE_MODULES_DIRECTORY {
+            continue;
+        }
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => list_files(&entry.path(), files),
+            Ok(t) if t.is_file() => {
+                files.insert(entry.path());
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,53 +1,199 @@
 use super::{
+    boundaries::check_boundaries,
+    config::EsteemConfig,
     constants::{
-        DEVELOPMENT_KEY, PACKAGE_JSON_BACKUP_FILE, PACKAGE_JSON_FILE, REQUIRED_KEY,
+        DEVELOPMENT_KEY, DOCKERFILE, DOCKERIGNORE_FILE, OPTIONAL_KEY, PACKAGE_JSON_FILE,
+        PEER_KEY, REQUIRED_KEY, WORKSPACE_FILE, WORKSPACE_PROTOCOL_VERSION,
     },
+    dependencies::{EsteemDependencies, RequirementScope},
+    docker::DockerContext,
+    export::{GraphExport, GraphFormat},
+    graph::{Direction, NxProject},
+    implicit::get_implicit_dependencies,
+    imports::get_imported_packages,
+    licenses::check_licenses,
+    lockfile::Lockfile,
     managers::PackageManager,
-    utils::{display_warning, get_project_dependencies},
-    workspace::EsteemWorkspace,
-    AddEsteemDevelopmentDependency, AddEsteemRequiredDependency, LibraryError,
-    RemoveEsteemDevelopmentDependency, RemoveEsteemRequiredDependency, WriteDependencies,
+    manifest::PackageJson,
+    node_modules::{
+        get_binaries, get_types_package, has_bundled_types, is_known, is_outdated,
+        is_published, Installation,
+    },
+    optimize::{get_suggestions, Suggestion},
+    publish::PublishablePackageJson,
+    sbom::{Sbom, SbomFormat},
+    size::{SizeFormat, SizeReport},
+    specifier::PackageSpecifier,
+    utils::{
+        display_warning, get_isolated_projects, get_isolation_graph,
+        get_project_dependencies, get_project_graph, isolate_projects,
+    },
+    workspace::{EsteemWorkspace, WorkspaceProtocol},
+    AddEsteemDevelopmentDependency, AddEsteemOptionalDependency, AddEsteemPeerDependency,
+    AddEsteemRequiredDependency, AddEsteemTargetDependency, LibraryError,
+    RemoveEsteemDevelopmentDependency, RemoveEsteemOptionalDependency,
+    RemoveEsteemPeerDependency, RemoveEsteemRequiredDependency,
+    RemoveEsteemTargetDependency, WriteDependencies,
 };
-use npm_package_json::Package;
 use std::{
     collections::{BTreeMap, BTreeSet},
-    env::current_dir,
-    fs::rename,
+    fs::{create_dir_all, rename, write},
     path::PathBuf,
+    slice::from_ref,
 };
 
-impl WriteDependencies for Package {
-    fn get_path(&self) -> PathBuf {
-        current_dir().unwrap().join(PACKAGE_JSON_FILE)
+/// adds a dependency to the requirement scope `scope` of a project or workspace
+fn add_to_scope<T>(target: &mut T, dependency: String, scope: RequirementScope)
+where
+    T: AddEsteemRequiredDependency
+        + AddEsteemDevelopmentDependency
+        + AddEsteemPeerDependency
+        + AddEsteemOptionalDependency,
+{
+    match scope {
+        RequirementScope::Required => target.add_required_dependency(dependency),
+        RequirementScope::Development => target.add_development_dependency(dependency),
+        RequirementScope::Peer => target.add_peer_dependency(dependency),
+        RequirementScope::Optional => target.add_optional_dependency(dependency),
+    }
+}
+
+/// removes a dependency from the requirement scope `scope` of a project or workspace
+fn remove_from_scope<T>(
+    target: &mut T,
+    dependency: String,
+    scope: RequirementScope,
+) -> Result<(), LibraryError>
+where
+    T: RemoveEsteemRequiredDependency
+        + RemoveEsteemDevelopmentDependency
+        + RemoveEsteemPeerDependency
+        + RemoveEsteemOptionalDependency,
+{
+    match scope {
+        RequirementScope::Required => target.remove_required_dependency(dependency),
+        RequirementScope::Development => target.remove_development_dependency(dependency),
+        RequirementScope::Peer => target.remove_peer_dependency(dependency),
+        RequirementScope::Optional => target.remove_optional_dependency(dependency),
+    }
+}
+
+/// parses the packages passed to `add`, only their names are declared while the complete
+/// specifiers are passed to the package manager
+fn parse_specifiers(to_add: &[String]) -> Result<Vec<PackageSpecifier>, LibraryError> {
+    to_add
+        .iter()
+        .map(|spec| {
+            let specifier = spec.parse::<PackageSpecifier>()?;
+            if specifier.name != specifier.raw {
+                info!("Declaring {:?} as {:?}", specifier.raw, specifier.name);
+            }
+            Ok(specifier)
+        })
+        .collect()
+}
+
+/// returns the `@types` packages of the `specifiers` that do not bundle their own types
+fn get_missing_types(specifiers: &[PackageSpecifier]) -> Vec<String> {
+    let mut ret = vec![];
+    for specifier in specifiers {
+        let name = &specifier.name;
+        let types = match get_types_package(name) {
+            Some(types) => types,
+            None => continue,
+        };
+        match has_bundled_types(name) {
+            Some(true) => info!("{name:?} bundles its own types"),
+            // not every package without bundled types has an `@types` package
+            Some(false) if is_known(&types) => ret.push(types),
+            Some(false) => match is_published(&types) {
+                Some(true) => ret.push(types),
+                Some(false) => warn!(
+                    "{name:?} does not bundle its own types and {types:?} does not exist, skipping it"
+                ),
+                None => warn!(
+                    "Could not check whether {types:?} exists in the registry, skipping it"
+                ),
+            },
+            None if is_known(&types) => ret.push(types),
+            None => warn!(
+                "{name:?} is not installed so it is unknown whether it bundles its own types, skipping {types:?}"
+            ),
+        }
+    }
+    ret
+}
+
+/// The options of `add` and `workspace add`
+#[derive(Debug)]
+pub struct AddOptions {
+    /// the requirement scope that the dependencies are added to
+    pub scope: RequirementScope,
+    /// add the dependencies to this target instead of a requirement scope
+    pub target: Option<String>,
+    /// install the exact version instead of a range
+    pub is_exact: bool,
+    /// skip calling the package manager
+    pub skip_package_manager: bool,
+    /// also add the `@types` packages of the dependencies without bundled types
+    pub with_types: bool,
+    /// extra arguments passed to the package manager
+    pub extra_args: Vec<String>,
+}
+
+impl AddOptions {
+    fn call_package_manager(
+        &self,
+        to_add: Vec<String>,
+        scope: RequirementScope,
+    ) -> Result<(), LibraryError> {
+        if !self.skip_package_manager {
+            let mut manager = PackageManager::get_command_executor(true).unwrap();
+            manager.add_dependencies(
+                to_add,
+                scope,
+                self.is_exact,
+                self.extra_args.clone(),
+            )?;
+            manager.execute_command()?;
+        }
+        Ok(())
     }
 }
 
 pub fn perform_add(
     project_name: String,
     to_add: Vec<String>,
-    is_development: bool,
-    skip_package_manager: bool,
+    options: AddOptions,
 ) -> Result<(), LibraryError> {
+    let specifiers = parse_specifiers(&to_add)?;
     let mut workspace = EsteemWorkspace::from_current_directory().unwrap();
     let project = workspace.get_project_mut(project_name).unwrap();
-    to_add.iter().for_each(|dependency| {
-        if is_development {
-            project.add_development_dependency(dependency.into())
-        } else {
-            project.add_required_dependency(dependency.into())
+    specifiers
+        .iter()
+        .for_each(|specifier| match &options.target {
+            Some(target) => project
+                .add_target_dependency(target.to_owned(), specifier.name.to_owned()),
+            None => add_to_scope(project, specifier.name.to_owned(), options.scope),
+        });
+    project.write_dependencies();
+    options.call_package_manager(to_add, options.scope)?;
+    if !options.with_types {
+        return Ok(());
+    }
+    // the packages have been installed by now, unless the package manager was skipped
+    let types = get_missing_types(&specifiers);
+    if types.is_empty() {
+        return Ok(());
+    }
+    types.iter().for_each(|types| match &options.target {
+        Some(target) => {
+            project.add_target_dependency(target.to_owned(), types.to_owned())
         }
+        None => project.add_development_dependency(types.to_owned()),
     });
     project.write_dependencies();
-    if !skip_package_manager {
-        let mut manager = PackageManager::get_command_executor(true).unwrap();
-        if is_development {
-            manager.add_development_dependencies(to_add);
-        } else {
-            manager.add_required_dependencies(to_add);
-        }
-        manager.execute_command();
-    }
-    Ok(())
+    options.call_package_manager(types, RequirementScope::Development)
 }
 
 pub fn perform_init() -> Result<(), LibraryError> {
@@ -59,80 +205,222 @@ pub fn perform_init() -> Result<(), LibraryError> {
     Ok(())
 }
 
+/// The options of `install-isolated`
+#[derive(Debug)]
+pub struct InstallIsolatedOptions {
+    /// the requirement scopes whose dependencies are written to `package.json`
+    pub scopes: BTreeSet<RequirementScope>,
+    /// call the package manager to install the isolated dependencies
+    pub install: bool,
+    /// do not update the lockfile while installing
+    pub frozen: bool,
+    /// only install the required dependencies
+    pub production: bool,
+    /// move the original `package.json` back once the dependencies are installed
+    pub restore_after: bool,
+    /// remove the overrides and resolutions that only apply to removed packages
+    pub prune_overrides: bool,
+    /// use the given projects as they are instead of calculating their upstream projects
+    pub without_graph: bool,
+    /// what is written for the internal packages of the workspace
+    pub workspace_protocol: WorkspaceProtocol,
+    /// do not write the development dependencies inferred from the targets of projects
+    pub skip_implicit: bool,
+    /// only write the dependencies needed to run these targets, instead of all targets
+    pub targets: Option<BTreeSet<String>>,
+    /// extra arguments passed to the package manager
+    pub extra_args: Vec<String>,
+}
+
+impl Default for InstallIsolatedOptions {
+    fn default() -> Self {
+        Self {
+            scopes: RequirementScope::all(),
+            install: false,
+            frozen: false,
+            production: false,
+            restore_after: false,
+            prune_overrides: false,
+            without_graph: false,
+            workspace_protocol: WorkspaceProtocol::default(),
+            skip_implicit: false,
+            targets: None,
+            extra_args: vec![],
+        }
+    }
+}
+
 pub fn perform_install_isolated(
     project_names: Vec<String>,
     call_script_executor: bool,
+    use_cache: bool,
+    options: InstallIsolatedOptions,
 ) -> Result<(), LibraryError> {
+    if !options.install && (options.frozen || options.production || options.restore_after)
+    {
+        return Err(LibraryError(
+            "`frozen`, `production` and `restore-after` need `install` to be enabled"
+                .into(),
+        ));
+    }
     let workspace = EsteemWorkspace::from_current_directory().unwrap();
-    let mut package_json_file =
-        Package::from_path(current_dir().unwrap().join(PACKAGE_JSON_FILE)).unwrap();
-    let mut to_install_dev_deps = BTreeSet::new();
-    let mut to_install_required_deps = BTreeSet::new();
-    info!("Calculating all dependent projects of {project_names:?}");
-    project_names.into_iter().for_each(|name| {
-        let dependent_projects = get_project_dependencies(&name, call_script_executor);
-        info!(
-            "{:?} depends on/is depended on by {:?} projects",
-            &name,
-            &dependent_projects.len()
-        );
-        dependent_projects.iter().for_each(|p| {
-            let deps = p.dependencies.clone();
-            to_install_dev_deps.extend(deps.development);
-            to_install_required_deps.extend(deps.required);
-        });
-    });
-    to_install_dev_deps.extend(workspace.dependencies.development);
-    info!(
-        "Number of {DEVELOPMENT_KEY} packages calculated: {:?}",
-        to_install_dev_deps.len()
-    );
-    to_install_required_deps.extend(workspace.dependencies.required);
+    let mut package_json_file = PackageJson::from_current_directory()?;
+    match options.without_graph {
+        true => info!("Using {project_names:?} without calculating the project graph"),
+        false => info!("Calculating all upstream projects of {project_names:?}"),
+    }
+    let dependent_projects = get_isolated_projects(
+        &workspace,
+        &project_names,
+        call_script_executor,
+        use_cache,
+        options.without_graph,
+    )?
+    .into_iter()
+    .map(|p| workspace.get_project(p).cloned())
+    .collect::<Result<Vec<_>, _>>()?;
     info!(
-        "Number of {REQUIRED_KEY} packages calculated: {:?}",
-        to_install_required_deps.len()
+        "{:?} depend on {:?} projects (including themselves)",
+        &project_names,
+        &dependent_projects.len()
     );
-    let workspace_dependencies = package_json_file
-        .dependencies
+    info!("Collecting the {:?} dependencies", options.scopes);
+    let dependencies = dependent_projects
+        .iter()
+        .map(|p| &p.dependencies)
+        .chain([&workspace.dependencies])
+        .map(|deps| workspace.resolve(deps));
+    let mut to_install = BTreeMap::<RequirementScope, BTreeSet<String>>::new();
+    for deps in dependencies {
+        for scope in &options.scopes {
+            to_install
+                .entry(*scope)
+                .or_default()
+                .extend(deps.get_scope(*scope).iter().cloned());
+        }
+        if options.scopes.contains(&RequirementScope::Development) {
+            to_install
+                .entry(RequirementScope::Development)
+                .or_default()
+                .extend(
+                    deps.get_target_dependencies(options.targets.as_ref())
+                        .cloned(),
+                );
+        }
+    }
+    // a package can be declared in any section of `package.json`, regardless of the
+    // scope it is written to
+    let workspace_dependencies = package_json_file.get_all_versions();
+    if !options.skip_implicit && options.scopes.contains(&RequirementScope::Development) {
+        let binaries = get_binaries(workspace_dependencies.keys());
+        let development = to_install.entry(RequirementScope::Development).or_default();
+        for project in &dependent_projects {
+            let implicit = get_implicit_dependencies(
+                project,
+                options.targets.as_ref(),
+                &binaries,
+                &workspace_dependencies,
+            );
+            for (package, reason) in implicit {
+                match workspace_dependencies.contains_key(&package) {
+                    true => {
+                        debug!("{:?} needs {package:?} for the {reason}", project.name);
+                        development.insert(package);
+                    }
+                    false => warn!(
+                        "{:?} needs {package:?} for the {reason} but it does not exist in {PACKAGE_JSON_FILE:?}",
+                        project.name
+                    ),
+                }
+            }
+        }
+    }
+    for (scope, packages) in &to_install {
+        info!(
+            "Number of {scope} packages calculated: {:?}",
+            packages.len()
+        );
+    }
+    let internal_packages = workspace.get_internal_packages();
+    let mut filtered = to_install
         .into_iter()
-        .chain(package_json_file.dev_dependencies.into_iter())
-        .collect::<BTreeMap<String, String>>();
-    let [filtered_dev_deps, filtered_required_deps] =
-        [&to_install_dev_deps, &to_install_required_deps].map(|dep_set| {
-            dep_set
-                .iter()
+        .map(|(scope, dep_set)| {
+            let mut versions = dep_set
+                .into_iter()
                 .map(|possible_package| {
-                    (
-                        possible_package.clone(),
-                        workspace_dependencies
-                            .get(&possible_package.clone())
-                            .cloned()
-                            .unwrap_or_else(|| {
-                                error!(
-                                    "{:?} does not exist in {:?}",
-                                    possible_package, PACKAGE_JSON_FILE
-                                );
-                                panic!("unrecoverable error");
-                            }),
-                    )
+                    let version = workspace_dependencies
+                        .get(&possible_package)
+                        .cloned()
+                        .or_else(|| {
+                            internal_packages
+                                .contains_key(&possible_package)
+                                .then(|| WORKSPACE_PROTOCOL_VERSION.to_owned())
+                        })
+                        .ok_or_else(|| {
+                            LibraryError(format!(
+                                "{possible_package:?} does not exist in {PACKAGE_JSON_FILE:?}"
+                            ))
+                        })?;
+                    Ok((possible_package, version))
                 })
-                .collect::<BTreeMap<String, String>>()
-        });
-    package_json_file.dependencies = filtered_required_deps;
-    package_json_file.dev_dependencies = filtered_dev_deps;
-    info!("Renaming {PACKAGE_JSON_FILE:?} to {PACKAGE_JSON_BACKUP_FILE:?}",);
-    rename(PACKAGE_JSON_FILE, PACKAGE_JSON_BACKUP_FILE).unwrap_or_else(|_| {
-        error!("Unable to create backup file, exiting early...");
-        panic!("unrecoverable error");
-    });
+                .collect::<Result<BTreeMap<String, String>, LibraryError>>()?;
+            options
+                .workspace_protocol
+                .apply(&mut versions, &internal_packages);
+            Ok((scope, versions))
+        })
+        .collect::<Result<BTreeMap<_, _>, LibraryError>>()?;
+    if options.prune_overrides {
+        let removed = workspace_dependencies
+            .keys()
+            .filter(|p| !filtered.values().any(|versions| versions.contains_key(*p)))
+            .cloned()
+            .collect();
+        let pruned = package_json_file.prune_overrides(&removed);
+        info!("Pruned the overrides of removed packages: {pruned:?}");
+    }
+    for scope in RequirementScope::all() {
+        package_json_file.set_dependencies(
+            scope.package_json_key(),
+            filtered.remove(&scope).unwrap_or_default(),
+        );
+    }
+    let backup_file = &EsteemConfig::get().backup_file;
+    info!("Renaming {PACKAGE_JSON_FILE:?} to {backup_file:?}");
+    rename(PACKAGE_JSON_FILE, backup_file).map_err(|err| {
+        LibraryError(format!(
+            "Could not create the backup file {backup_file:?}: {err}"
+        ))
+    })?;
     package_json_file.write_dependencies();
-    warn!("Please run your package manager's install command to install the isolated dependencies.");
-    Ok(())
+    if !options.install {
+        warn!(
+            "Please run your package manager's install command to install the isolated dependencies."
+        );
+        return Ok(());
+    }
+    let mut manager = PackageManager::get_command_executor(true)?;
+    manager.install_dependencies(
+        options.frozen,
+        options.production,
+        options.extra_args,
+    )?;
+    let installed = manager.execute_command();
+    if options.restore_after {
+        info!("Restoring {PACKAGE_JSON_FILE:?} from {backup_file:?}");
+        rename(backup_file, PACKAGE_JSON_FILE).map_err(|err| {
+            LibraryError(format!(
+                "Could not restore {PACKAGE_JSON_FILE:?} from {backup_file:?}: {err}"
+            ))
+        })?;
+    }
+    installed
 }
 
 pub fn perform_remove(
     project_name: String,
     to_remove: Vec<String>,
+    extra_args: Vec<String>,
 ) -> Result<(), LibraryError> {
     let mut workspace = EsteemWorkspace::from_current_directory().unwrap();
     let project = workspace.get_project_mut(project_name).unwrap();
@@ -146,6 +434,17 @@ pub fn perform_remove(
             Ok(_) => should_proceed = true,
             Err(_) => display_warning(REQUIRED_KEY, dependency, &project.get_path()),
         }
+        match project.remove_peer_dependency(dependency.into()) {
+            Ok(_) => should_proceed = true,
+            Err(_) => display_warning(PEER_KEY, dependency, &project.get_path()),
+        }
+        match project.remove_optional_dependency(dependency.into()) {
+            Ok(_) => should_proceed = true,
+            Err(_) => display_warning(OPTIONAL_KEY, dependency, &project.get_path()),
+        }
+        if project.remove_target_dependency(dependency.into()).is_ok() {
+            should_proceed = true;
+        }
         if !should_proceed {
             error!(
                 "{:?} not found in {:?}, exiting early without writing to file",
@@ -163,39 +462,50 @@ pub fn perform_remove(
         );
     } else {
         let mut manager = PackageManager::get_command_executor(true).unwrap();
-        manager.remove_dependencies(packages_to_remove);
-        manager.execute_command();
+        manager.remove_dependencies(packages_to_remove, extra_args)?;
+        manager.execute_command()?;
     }
     Ok(())
 }
 
 pub fn perform_workspace_add(
     to_add: Vec<String>,
-    is_development: bool,
-    skip_package_manager: bool,
+    options: AddOptions,
 ) -> Result<(), LibraryError> {
+    let specifiers = parse_specifiers(&to_add)?;
     let mut workspace = EsteemWorkspace::from_current_directory().unwrap();
-    to_add.iter().for_each(|dependency| {
-        if is_development {
-            workspace.add_development_dependency(dependency.to_string())
-        } else {
-            workspace.add_required_dependency(dependency.to_string())
+    specifiers
+        .iter()
+        .for_each(|specifier| match &options.target {
+            Some(target) => workspace
+                .add_target_dependency(target.to_owned(), specifier.name.to_owned()),
+            None => {
+                add_to_scope(&mut workspace, specifier.name.to_owned(), options.scope)
+            }
+        });
+    workspace.write_dependencies();
+    options.call_package_manager(to_add, options.scope)?;
+    if !options.with_types {
+        return Ok(());
+    }
+    let types = get_missing_types(&specifiers);
+    if types.is_empty() {
+        return Ok(());
+    }
+    types.iter().for_each(|types| match &options.target {
+        Some(target) => {
+            workspace.add_target_dependency(target.to_owned(), types.to_owned())
         }
+        None => workspace.add_development_dependency(types.to_owned()),
     });
     workspace.write_dependencies();
-    if !skip_package_manager {
-        let mut manager = PackageManager::get_command_executor(true).unwrap();
-        if is_development {
-            manager.add_development_dependencies(to_add);
-        } else {
-            manager.add_required_dependencies(to_add);
-        }
-        manager.execute_command();
-    }
-    Ok(())
+    options.call_package_manager(types, RequirementScope::Development)
 }
 
-pub fn perform_workspace_remove(to_remove: Vec<String>) -> Result<(), LibraryError> {
+pub fn perform_workspace_remove(
+    to_remove: Vec<String>,
+    extra_args: Vec<String>,
+) -> Result<(), LibraryError> {
     let mut workspace = EsteemWorkspace::from_current_directory().unwrap();
     for dependency in to_remove.iter() {
         let mut should_proceed = false;
@@ -207,6 +517,20 @@ pub fn perform_workspace_remove(to_remove: Vec<String>) -> Result<(), LibraryErr
             Ok(_) => should_proceed = true,
             Err(_) => display_warning(REQUIRED_KEY, dependency, &workspace.get_path()),
         }
+        match workspace.remove_peer_dependency(dependency.into()) {
+            Ok(_) => should_proceed = true,
+            Err(_) => display_warning(PEER_KEY, dependency, &workspace.get_path()),
+        }
+        match workspace.remove_optional_dependency(dependency.into()) {
+            Ok(_) => should_proceed = true,
+            Err(_) => display_warning(OPTIONAL_KEY, dependency, &workspace.get_path()),
+        }
+        if workspace
+            .remove_target_dependency(dependency.into())
+            .is_ok()
+        {
+            should_proceed = true;
+        }
         if !should_proceed {
             error!(
                 "{:?} not found in {:?}, exiting early without writing to file",
@@ -220,20 +544,588 @@ pub fn perform_workspace_remove(to_remove: Vec<String>) -> Result<(), LibraryErr
     let packages_to_remove = workspace.get_dependencies_to_remove(to_remove);
     if !packages_to_remove.is_empty() {
         let mut manager = PackageManager::get_command_executor(true).unwrap();
-        manager.remove_dependencies(packages_to_remove);
-        manager.execute_command();
+        manager.remove_dependencies(packages_to_remove, extra_args)?;
+        manager.execute_command()?;
     }
     Ok(())
 }
 
+/// installs every package of `package.json` using the package manager
+pub fn perform_install(
+    is_frozen: bool,
+    is_production: bool,
+    extra_args: Vec<String>,
+) -> Result<(), LibraryError> {
+    let mut manager = PackageManager::get_command_executor(true)?;
+    manager.install_dependencies(is_frozen, is_production, extra_args)?;
+    manager.execute_command()?;
+    Ok(())
+}
+
 pub fn utils_get_dependencies(
     project_name: String,
     call_script_executor: bool,
+    direction: Direction,
+    use_cache: bool,
 ) -> Result<Vec<String>, LibraryError> {
-    Ok(
-        get_project_dependencies(&project_name, call_script_executor)
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    Ok(get_project_dependencies(
+        &workspace,
+        &[project_name],
+        call_script_executor,
+        direction,
+        use_cache,
+    )?
+    .into_iter()
+    .map(|p| p.name)
+    .collect::<Vec<_>>())
+}
+
+pub fn perform_graph(
+    project_name: Option<String>,
+    direction: Direction,
+    format: GraphFormat,
+    with_dependencies: bool,
+    call_script_executor: bool,
+    use_cache: bool,
+) -> Result<String, LibraryError> {
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let graph = get_project_graph(
+        &workspace,
+        project_name.as_ref(),
+        call_script_executor,
+        use_cache,
+    )?;
+    let projects = match &project_name {
+        Some(name) => graph.get_project_dependencies(name, direction)?,
+        None => graph.get_project_names(),
+    };
+    let mut export = GraphExport::new(&graph, &projects);
+    if with_dependencies {
+        export.annotate(&workspace);
+    }
+    Ok(export.render(format))
+}
+
+/// returns the cycles in the project graph, except for the ones whose projects are
+/// exactly one of the `allowed` sets
+pub fn perform_cycles(
+    allowed: Vec<BTreeSet<String>>,
+    call_script_executor: bool,
+    use_cache: bool,
+) -> Result<Vec<Vec<String>>, LibraryError> {
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let graph = get_project_graph(&workspace, None, call_script_executor, use_cache)?;
+    let cycles = graph.get_cycles();
+    info!("Found {:?} cycles in the project graph", cycles.len());
+    Ok(cycles
+        .into_iter()
+        .filter(|cycle| {
+            let projects = cycle.iter().cloned().collect::<BTreeSet<_>>();
+            let is_allowed = allowed.contains(&projects);
+            if is_allowed {
+                info!("Ignoring allowed cycle: {}", cycle.join(" -> "));
+            }
+            !is_allowed
+        })
+        .collect())
+}
+
+/// returns every violation of the `boundaries` rules of the configuration
+pub fn perform_boundaries(
+    call_script_executor: bool,
+    use_cache: bool,
+) -> Result<Vec<String>, LibraryError> {
+    let constraints = &EsteemConfig::get().boundaries.dep_constraints;
+    if constraints.is_empty() {
+        warn!(
+            "No `boundaries.depConstraints` found in the configuration, nothing to check"
+        );
+        return Ok(vec![]);
+    }
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let graph = get_project_graph(&workspace, None, call_script_executor, use_cache)?;
+    Ok(check_boundaries(constraints, &graph, &workspace))
+}
+
+/// writes the `package.json` of a project to its output directory (or `output`),
+/// returning the path it was written to
+pub fn perform_generate_package_json(
+    project_name: String,
+    output: Option<PathBuf>,
+    use_caret: bool,
+    call_script_executor: bool,
+    use_cache: bool,
+) -> Result<PathBuf, LibraryError> {
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let project = workspace.get_project(project_name.clone())?;
+    let directory = output.or_else(|| project.get_output_path()).ok_or_else(|| {
+        LibraryError(format!(
+            "{project_name:?} does not have a `build` target with an `outputPath`, please pass `--output`"
+        ))
+    })?;
+    let graph = get_project_graph(
+        &workspace,
+        Some(&project_name),
+        call_script_executor,
+        use_cache,
+    )?;
+    let root_package_json = PackageJson::from_current_directory()?;
+    let path = directory.join(PACKAGE_JSON_FILE);
+    let package_json = PublishablePackageJson::new(
+        &project_name,
+        path.clone(),
+        &workspace,
+        &graph,
+        &root_package_json,
+        use_caret,
+    )?;
+    create_dir_all(&directory)
+        .map_err(|err| LibraryError(format!("Could not create {directory:?}: {err}")))?;
+    package_json.write_dependencies();
+    Ok(path)
+}
+
+/// writes the `Dockerfile` of a project and its `.dockerignore` to the root of the
+/// project (or `output`), returning the paths they were written to
+pub fn perform_docker(
+    project_name: String,
+    output: Option<PathBuf>,
+    base_image: String,
+    call_script_executor: bool,
+    use_cache: bool,
+) -> Result<Vec<PathBuf>, LibraryError> {
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let roots = get_isolated_projects(
+        &workspace,
+        from_ref(&project_name),
+        call_script_executor,
+        use_cache,
+        false,
+    )?
+    .into_iter()
+    .filter_map(|p| workspace.projects.get(&p).map(|root| (p, root.to_owned())))
+    .collect::<BTreeMap<_, _>>();
+    let stubs = workspace
+        .projects
+        .iter()
+        .filter(|(name, _)| !roots.contains_key(*name))
+        .map(|(_, root)| root.to_owned())
+        .collect();
+    let has_internal_packages = workspace
+        .get_internal_packages()
+        .values()
+        .any(|package| roots.contains_key(&package.project));
+    let context = DockerContext::new(
+        &project_name,
+        roots,
+        stubs,
+        base_image,
+        has_internal_packages,
+    )?;
+    let directory = output.unwrap_or_else(|| context.get_project_root().to_path_buf());
+    create_dir_all(&directory)
+        .map_err(|err| LibraryError(format!("Could not create {directory:?}: {err}")))?;
+    let dockerfile = directory.join(DOCKERFILE);
+    let dockerignore = directory.join(DOCKERIGNORE_FILE);
+    for (path, contents) in [
+        (&dockerfile, context.render_dockerfile(&dockerfile)),
+        (&dockerignore, context.render_dockerignore()),
+    ] {
+        info!("Writing {path:?}");
+        write(path, contents)
+            .map_err(|err| LibraryError(format!("Could not write {path:?}: {err}")))?;
+    }
+    Ok(vec![dockerfile, dockerignore])
+}
+
+/// returns the packages that the projects (or all projects if none are given) declare or
+/// need to run their targets, but which do not exist in the root `package.json`
+pub fn perform_check(project_names: Vec<String>) -> Result<Vec<String>, LibraryError> {
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let package_json_file = PackageJson::from_current_directory()?;
+    let versions = package_json_file.get_all_versions();
+    let internal_packages = workspace.get_internal_packages();
+    let binaries = get_binaries(versions.keys());
+    let projects = match project_names.is_empty() {
+        true => workspace.all_projects_rep.iter().collect(),
+        false => project_names
+            .into_iter()
+            .map(|p| workspace.get_project(p))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    let is_missing = |package: &String| {
+        !versions.contains_key(package) && !internal_packages.contains_key(package)
+    };
+    let mut missing = vec![];
+    for project in projects {
+        for group in &project.dependencies.groups {
+            if !workspace.groups.contains_key(group) {
+                missing.push(format!(
+                    "{:?} includes the dependency group {group:?} which does not exist in {WORKSPACE_FILE:?}",
+                    project.name
+                ));
+            }
+        }
+        let dependencies = workspace.resolve(&project.dependencies);
+        for scope in RequirementScope::all() {
+            for package in dependencies.get_scope(scope) {
+                if is_missing(package) {
+                    missing.push(format!(
+                        "{:?} declares the {scope} package {package:?} which does not exist in {PACKAGE_JSON_FILE:?}",
+                        project.name
+                    ));
+                }
+            }
+        }
+        let implicit = get_implicit_dependencies(project, None, &binaries, &versions);
+        for (package, reason) in implicit {
+            if is_missing(&package) {
+                missing.push(format!(
+                    "{:?} needs {package:?} for the {reason} which does not exist in {PACKAGE_JSON_FILE:?}",
+                    project.name
+                ));
+            }
+        }
+    }
+    Ok(missing)
+}
+
+/// returns the dependencies of a project (or the workspace if none is given) with their
+/// groups resolved, one per line along with the groups they are included from
+pub fn perform_list(project_name: Option<String>) -> Result<Vec<String>, LibraryError> {
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let dependencies = match project_name {
+        Some(project_name) => &workspace.get_project(project_name)?.dependencies,
+        None => &workspace.dependencies,
+    };
+    // the sections in the order they are listed, each package is mapped to the groups it
+    // is included from (`None` if it is declared directly)
+    let mut sections = Vec::<(String, BTreeMap<String, BTreeSet<Option<String>>>)>::new();
+    let mut collect = |declared: &EsteemDependencies, group: Option<&String>| {
+        let scopes = RequirementScope::all()
+            .into_iter()
+            .map(|scope| (scope.to_string(), declared.get_scope(scope)));
+        let targets = declared
+            .targets
+            .iter()
+            .map(|(target, packages)| (format!("{DEVELOPMENT_KEY}[{target}]"), packages));
+        for (section, packages) in scopes.chain(targets) {
+            let index = match sections.iter().position(|(s, _)| *s == section) {
+                Some(index) => index,
+                None => {
+                    sections.push((section, BTreeMap::new()));
+                    sections.len() - 1
+                }
+            };
+            for package in packages {
+                sections[index]
+                    .1
+                    .entry(package.to_owned())
+                    .or_default()
+                    .insert(group.cloned());
+            }
+        }
+    };
+    collect(dependencies, None);
+    for name in &dependencies.groups {
+        match workspace.groups.get(name) {
+            Some(group) => collect(group, Some(name)),
+            None => warn!("{name:?} is not a dependency group of {WORKSPACE_FILE:?}"),
+        }
+    }
+    Ok(sections
+        .into_iter()
+        .flat_map(|(section, packages)| {
+            packages.into_iter().map(move |(package, groups)| {
+                match groups.contains(&None) {
+                    true => format!("{section} {package}"),
+                    false => {
+                        let groups = groups.into_iter().flatten().collect::<Vec<_>>();
+                        format!("{section} {package} (group {})", groups.join(", "))
+                    }
+                }
+            })
+        })
+        .collect())
+}
+
+/// returns the report of the disk footprint of `project_names` (or of all projects if
+/// none are given): the packages installed for each of them in isolation (see
+/// [`perform_install_isolated`]) compared to the whole workspace, along with the `top`
+/// heaviest packages
+pub fn perform_size(
+    project_names: Vec<String>,
+    format: SizeFormat,
+    top: usize,
+    call_script_executor: bool,
+    use_cache: bool,
+) -> Result<String, LibraryError> {
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let versions = PackageJson::from_current_directory()?.get_all_versions();
+    let mut installation = Installation::from_current_directory()?;
+    if is_outdated() {
+        warn!("The lockfile changed since the packages were installed, the sizes might not be accurate");
+    }
+    let project_names = match project_names.is_empty() {
+        true => workspace
+            .all_projects_rep
+            .iter()
+            .map(|p| p.name.to_owned())
+            .collect(),
+        false => project_names,
+    };
+    let full = installation.get_closure(versions.keys());
+    let binaries = get_binaries(versions.keys());
+    // the same graph is used for every project so that it is only calculated once
+    let graph =
+        get_isolation_graph(&workspace, &project_names, call_script_executor, use_cache)?;
+    let mut projects = BTreeMap::new();
+    for project_name in project_names {
+        let packages = get_isolated_packages(
+            &workspace,
+            &graph,
+            &project_name,
+            &versions,
+            Some(&binaries),
+        )?;
+        projects.insert(project_name, installation.get_closure(&packages));
+    }
+    Ok(SizeReport::new(&installation, &full, &projects, top).render(format))
+}
+
+/// returns the packages of the root `package.json` (`versions`) that `project_name`
+/// needs in isolation: the ones declared by its upstream projects and the workspace,
+/// along with the ones needed to run their targets if `binaries` are given. Otherwise,
+/// only the packages needed in production are returned.
+fn get_isolated_packages(
+    workspace: &EsteemWorkspace,
+    graph: &NxProject,
+    project_name: &String,
+    versions: &BTreeMap<String, String>,
+    binaries: Option<&BTreeMap<String, String>>,
+) -> Result<BTreeSet<String>, LibraryError> {
+    let isolated = isolate_projects(workspace, from_ref(project_name), Some(graph))?;
+    let get_packages = |deps: &EsteemDependencies| -> Vec<String> {
+        let deps = workspace.resolve(deps);
+        match binaries {
+            Some(_) => deps.get_all_dependencies(),
+            None => [
+                RequirementScope::Required,
+                RequirementScope::Peer,
+                RequirementScope::Optional,
+            ]
             .into_iter()
-            .map(|p| p.name)
-            .collect::<Vec<_>>(),
+            .flat_map(|scope| deps.get_scope(scope).iter().cloned())
+            .collect(),
+        }
+    };
+    let mut ret = get_packages(&workspace.dependencies)
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    for name in isolated {
+        let project = workspace.get_project(name)?;
+        ret.extend(get_packages(&project.dependencies));
+        if let Some(binaries) = binaries {
+            ret.extend(
+                get_implicit_dependencies(project, None, binaries, versions).into_keys(),
+            );
+        }
+    }
+    // internal packages are not installed from the registry
+    ret.retain(|package| versions.contains_key(package));
+    debug!("{project_name:?} needs {} packages", ret.len());
+    Ok(ret)
+}
+
+/// checks the licenses of the packages installed for `project_names` (or for all projects
+/// if none are given) in isolation against the policies of the configuration and returns
+/// a description of each violation. Only the packages needed in production are checked
+/// if `production` is set.
+pub fn perform_licenses(
+    project_names: Vec<String>,
+    production: bool,
+    call_script_executor: bool,
+    use_cache: bool,
+) -> Result<Vec<String>, LibraryError> {
+    let policies = &EsteemConfig::get().licenses.policies;
+    if policies.is_empty() {
+        warn!("There are no policies in `licenses.policies` of the configuration");
+        return Ok(vec![]);
+    }
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let versions = PackageJson::from_current_directory()?.get_all_versions();
+    let mut installation = Installation::from_current_directory()?;
+    if is_outdated() {
+        warn!("The lockfile changed since the packages were installed, the licenses might not be accurate");
+    }
+    let binaries = get_binaries(versions.keys());
+    let project_names = match project_names.is_empty() {
+        true => workspace
+            .all_projects_rep
+            .iter()
+            .map(|p| p.name.to_owned())
+            .collect(),
+        false => project_names,
+    };
+    // the same graph is used for every project so that it is only calculated once
+    let graph =
+        get_isolation_graph(&workspace, &project_names, call_script_executor, use_cache)?;
+    let mut violations = vec![];
+    for project_name in project_names {
+        let packages = get_isolated_packages(
+            &workspace,
+            &graph,
+            &project_name,
+            &versions,
+            (!production).then_some(&binaries),
+        )?;
+        let closure = installation.get_closure(&packages);
+        let direct = packages
+            .iter()
+            .map(|package| {
+                (
+                    package.to_owned(),
+                    installation.get_closure(from_ref(package)),
+                )
+            })
+            .collect();
+        info!(
+            "Checking the licenses of {} packages of {project_name:?}",
+            closure.len()
+        );
+        let project = workspace.get_project(project_name)?;
+        violations.extend(check_licenses(
+            policies,
+            project,
+            &installation,
+            &closure,
+            &direct,
+        ));
+    }
+    Ok(violations)
+}
+
+/// returns the software bill of materials of `project_name`: every package installed for
+/// it in isolation (only the ones needed in production if `production` is set) with
+/// their versions and licenses from `node_modules` and their integrities from the
+/// lockfile
+pub fn perform_sbom(
+    project_name: String,
+    format: SbomFormat,
+    production: bool,
+    call_script_executor: bool,
+    use_cache: bool,
+) -> Result<String, LibraryError> {
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let versions = PackageJson::from_current_directory()?.get_all_versions();
+    let mut installation = Installation::from_current_directory()?;
+    if is_outdated() {
+        warn!("The lockfile changed since the packages were installed, the bill of materials might not be accurate");
+    }
+    let lockfile = Lockfile::from_current_directory()?;
+    let binaries = get_binaries(versions.keys());
+    let graph = get_isolation_graph(
+        &workspace,
+        from_ref(&project_name),
+        call_script_executor,
+        use_cache,
+    )?;
+    let packages = get_isolated_packages(
+        &workspace,
+        &graph,
+        &project_name,
+        &versions,
+        (!production).then_some(&binaries),
+    )?;
+    let direct = packages
+        .iter()
+        .filter_map(|package| {
+            let directory = installation.resolve(package);
+            if directory.is_none() {
+                warn!("{package:?} is not installed, it is left out of the bill of materials");
+            }
+            directory
+        })
+        .collect();
+    let closure = installation.get_closure(&packages);
+    info!(
+        "{project_name:?} needs {} installed packages",
+        closure.len()
+    );
+    Ok(
+        Sbom::new(&project_name, &installation, &lockfile, &direct, &closure)
+            .render(format),
     )
 }
+
+/// returns the suggestions to move dependencies between the workspace and the projects,
+/// applying them if `apply` is set
+pub fn perform_optimize(
+    max_users: usize,
+    min_share: f64,
+    apply: bool,
+) -> Result<Vec<String>, LibraryError> {
+    let mut workspace = EsteemWorkspace::from_current_directory()?;
+    let versions = PackageJson::from_current_directory()?.get_all_versions();
+    let binaries = get_binaries(versions.keys());
+    info!("Scanning the sources of all projects for the packages they use");
+    let usage = workspace
+        .all_projects_rep
+        .iter()
+        .map(|project| {
+            let mut used = workspace
+                .projects
+                .get(&project.name)
+                .map(|root| get_imported_packages(root))
+                .unwrap_or_default();
+            let implicit = get_implicit_dependencies(project, None, &binaries, &versions);
+            used.extend(implicit.into_keys());
+            used.extend(
+                workspace
+                    .resolve(&project.dependencies)
+                    .get_all_dependencies(),
+            );
+            (project.name.to_owned(), used)
+        })
+        .collect();
+    let suggestions = get_suggestions(&workspace, &usage, max_users, min_share);
+    if apply && !suggestions.is_empty() {
+        let mut changed = BTreeSet::new();
+        for suggestion in &suggestions {
+            match suggestion {
+                Suggestion::MoveToProjects {
+                    package,
+                    scope,
+                    projects,
+                } => {
+                    remove_from_scope(&mut workspace, package.to_owned(), *scope)?;
+                    for project in projects {
+                        let project = workspace.get_project_mut(project.to_owned())?;
+                        add_to_scope(project, package.to_owned(), *scope);
+                    }
+                    changed.extend(projects.iter().cloned());
+                }
+                Suggestion::MoveToWorkspace {
+                    package,
+                    scope,
+                    projects,
+                } => {
+                    for project in projects {
+                        let project = workspace.get_project_mut(project.to_owned())?;
+                        // packages included from a dependency group are not declared by
+                        // the project itself
+                        remove_from_scope(project, package.to_owned(), *scope).ok();
+                    }
+                    add_to_scope(&mut workspace, package.to_owned(), *scope);
+                    changed.extend(projects.iter().cloned());
+                }
+            }
+        }
+        workspace.write_dependencies();
+        for project in changed {
+            workspace.get_project(project)?.write_dependencies();
+        }
+    }
+    Ok(suggestions.iter().map(ToString::to_string).collect())
+}
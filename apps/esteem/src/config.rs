@@ -0,0 +1,483 @@
+use super::{
+    constants::{CONFIG_FILE, CONFIG_TOML_FILE, NX_FILE, PACKAGE_JSON_BACKUP_FILE},
+    dependencies::RequirementScope,
+    export::GraphFormat,
+    graph::Direction,
+    managers::PackageManager,
+    sbom::SbomFormat,
+    size::SizeFormat,
+    workspace::WorkspaceProtocol,
+    LibraryError,
+};
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env::current_dir,
+    fs::read_to_string,
+    path::PathBuf,
+};
+
+/// The key in `nx.json` that can hold the configuration
+const NX_CONFIG_KEY: &str = "esteem";
+
+static CONFIG: OnceCell<EsteemConfig> = OnceCell::new();
+
+/// The configuration of esteem, read from the root of the workspace
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct EsteemConfig {
+    /// the package manager to use instead of guessing it from the lockfile, either one of
+    /// `npm`, `yarn` and `pnpm` or the name of one of the `managers`
+    pub package_manager: Option<String>,
+    /// custom package managers that can be referred to by `packageManager`
+    pub managers: BTreeMap<String, PackageManager>,
+    /// whether to call NX via the script executor of the package manager by default
+    pub call_script_executor: bool,
+    /// the name of the file that `package.json` is backed up to by `install-isolated`
+    pub backup_file: String,
+    /// how the project graph is calculated
+    pub graph: GraphConfig,
+    /// the default values for the options of each command
+    pub commands: CommandDefaults,
+    /// the rules used by the `boundaries` command
+    pub boundaries: BoundariesConfig,
+    /// the policies used by the `licenses` command
+    pub licenses: LicensesConfig,
+    /// the names and versions used by `generate-package-json`
+    pub publish: PublishConfig,
+}
+
+impl Default for EsteemConfig {
+    fn default() -> Self {
+        Self {
+            package_manager: None,
+            managers: BTreeMap::new(),
+            call_script_executor: false,
+            backup_file: PACKAGE_JSON_BACKUP_FILE.to_owned(),
+            graph: GraphConfig::default(),
+            commands: CommandDefaults::default(),
+            boundaries: BoundariesConfig::default(),
+            licenses: LicensesConfig::default(),
+            publish: PublishConfig::default(),
+        }
+    }
+}
+
+impl EsteemConfig {
+    /// reads and validates the configuration, this should be called once at start-up
+    pub fn load() -> Result<&'static Self, LibraryError> {
+        let config = Self::from_current_directory()?;
+        config.validate()?;
+        Ok(CONFIG.get_or_init(|| config))
+    }
+
+    /// returns the loaded configuration, or the defaults if it has not been loaded
+    pub fn get() -> &'static Self {
+        CONFIG.get_or_init(Self::default)
+    }
+
+    /// reads the configuration from `esteem.json`, `esteem.toml` or the `esteem` key of
+    /// `nx.json` (in that order), falling back to the defaults if none of them exist
+    fn from_current_directory() -> Result<Self, LibraryError> {
+        let root = current_dir().unwrap();
+        let parse_error = |file: &str, err: String| {
+            LibraryError(format!("Could not parse {file:?}: {err}"))
+        };
+        if let Ok(data) = read_to_string(root.join(CONFIG_FILE)) {
+            trace!("Reading configuration from {CONFIG_FILE:?}");
+            return serde_json::from_str(&data)
+                .map_err(|err| parse_error(CONFIG_FILE, err.to_string()));
+        }
+        if let Ok(data) = read_to_string(root.join(CONFIG_TOML_FILE)) {
+            trace!("Reading configuration from {CONFIG_TOML_FILE:?}");
+            return toml::from_str(&data)
+                .map_err(|err| parse_error(CONFIG_TOML_FILE, err.to_string()));
+        }
+        if let Ok(data) = read_to_string(root.join(NX_FILE)) {
+            let mut nx_config: Value = serde_json::from_str(&data)
+                .map_err(|err| parse_error(NX_FILE, err.to_string()))?;
+            if let Some(config) = nx_config.get_mut(NX_CONFIG_KEY) {
+                trace!(
+                    "Reading configuration from the {NX_CONFIG_KEY:?} key of {NX_FILE:?}"
+                );
+                return serde_json::from_value(config.take())
+                    .map_err(|err| parse_error(NX_FILE, err.to_string()));
+            }
+        }
+        trace!("No configuration found, using the default configuration");
+        Ok(Self::default())
+    }
+
+    fn validate(&self) -> Result<(), LibraryError> {
+        if let Some(name) = &self.package_manager {
+            if PackageManager::builtin(name).is_none()
+                && !self.managers.contains_key(name)
+            {
+                return Err(LibraryError(format!(
+                    "Unknown package manager {name:?}, it must be one of `npm`, `yarn`, `pnpm` or a key of `managers`"
+                )));
+            }
+        }
+        for (name, manager) in &self.managers {
+            manager.validate(name)?;
+        }
+        if self.backup_file.is_empty() {
+            return Err(LibraryError("`backupFile` can not be empty".into()));
+        }
+        if self.graph.provider == GraphProvider::File && self.graph.path.is_none() {
+            return Err(LibraryError(
+                "`graph.path` is required when `graph.provider` is `file`".into(),
+            ));
+        }
+        for policy in &self.licenses.policies {
+            if policy.allow.is_none() && policy.deny.is_empty() {
+                return Err(LibraryError(
+                    "Every policy of `licenses.policies` needs `allow` or `deny`".into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How the project graph is obtained
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphProvider {
+    /// by calling `nx graph`
+    Nx,
+    /// by reading a graph previously written by `nx graph --file`
+    File,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GraphConfig {
+    pub provider: GraphProvider,
+    /// the path of the graph, used by the `file` provider
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+impl Default for GraphConfig {
+    fn default() -> Self {
+        Self {
+            provider: GraphProvider::Nx,
+            path: None,
+        }
+    }
+}
+
+/// How projects are named and versioned in the `package.json` of their output directory
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct PublishConfig {
+    /// the name of a project, `{project}` is replaced by the name of the project in NX
+    pub name_template: String,
+    /// the version of all projects
+    pub version: String,
+    /// the name and version of individual projects, taking precedence over the above
+    pub projects: BTreeMap<String, PublishProject>,
+}
+
+impl Default for PublishConfig {
+    fn default() -> Self {
+        Self {
+            name_template: "{project}".to_owned(),
+            version: "0.0.0".to_owned(),
+            projects: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PublishProject {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// The defaults for the options of each command, used when they are not passed
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct CommandDefaults {
+    pub add: AddDefaults,
+    pub install: InstallDefaults,
+    pub install_isolated: InstallIsolatedDefaults,
+    pub get_dependencies: GetDependenciesDefaults,
+    pub graph: GraphDefaults,
+    pub cycles: CyclesDefaults,
+    pub boundaries: CacheDefaults,
+    pub generate_package_json: GeneratePackageJsonDefaults,
+    pub docker: DockerDefaults,
+    pub optimize: OptimizeDefaults,
+    pub size: SizeDefaults,
+    pub sbom: SbomDefaults,
+    pub licenses: LicensesDefaults,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct AddDefaults {
+    /// skip calling the package manager
+    pub skip: bool,
+    /// install the exact version instead of a range
+    pub exact: bool,
+    /// also add the `@types` packages of the dependencies without bundled types
+    pub types: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct InstallDefaults {
+    /// do not update the lockfile while installing
+    pub frozen: bool,
+    /// only install the required dependencies
+    pub production: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct InstallIsolatedDefaults {
+    pub no_cache: bool,
+    /// the requirement scopes whose dependencies are written to `package.json`
+    pub scopes: Option<BTreeSet<RequirementScope>>,
+    /// call the package manager once `package.json` is written
+    pub install: bool,
+    pub frozen: bool,
+    pub production: bool,
+    /// move the original `package.json` back once the dependencies are installed
+    pub restore_after: bool,
+    /// remove the overrides and resolutions that only apply to removed packages
+    pub prune_overrides: bool,
+    /// what is written for the internal packages of the workspace
+    pub workspace_protocol: Option<WorkspaceProtocol>,
+    /// do not write the development dependencies inferred from the targets of projects
+    pub skip_implicit: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct GeneratePackageJsonDefaults {
+    pub no_cache: bool,
+    /// turn exact versions into caret ranges
+    pub caret: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct OptimizeDefaults {
+    /// the most projects that may use a workspace dependency for it to be moved into them
+    pub max_users: Option<usize>,
+    /// the least share of projects that must declare a dependency for it to be moved into
+    /// the workspace
+    pub min_share: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct SizeDefaults {
+    pub no_cache: bool,
+    pub format: Option<SizeFormat>,
+    /// the number of the heaviest packages to list
+    pub top: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct LicensesDefaults {
+    pub no_cache: bool,
+    /// only check the packages needed in production
+    pub production: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct SbomDefaults {
+    pub no_cache: bool,
+    pub format: Option<SbomFormat>,
+    /// only list the packages needed in production
+    pub production: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct DockerDefaults {
+    pub no_cache: bool,
+    /// the image that every stage of the `Dockerfile` is based on
+    pub base_image: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct CacheDefaults {
+    /// calculate the project graph again instead of using the cached one
+    pub no_cache: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct GetDependenciesDefaults {
+    pub no_cache: bool,
+    pub direction: Option<Direction>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct GraphDefaults {
+    pub no_cache: bool,
+    pub direction: Option<Direction>,
+    pub format: Option<GraphFormat>,
+    pub with_dependencies: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct CyclesDefaults {
+    pub no_cache: bool,
+    /// the projects of known cycles to ignore
+    pub allow: Vec<BTreeSet<String>>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BoundariesConfig {
+    /// the constraints that each project has to satisfy
+    #[serde(default)]
+    pub dep_constraints: Vec<DepConstraint>,
+}
+
+/// A rule that restricts what projects with a given tag may depend on, modelled after
+/// NX's `@nrwl/nx/enforce-module-boundaries` lint rule
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DepConstraint {
+    /// the tag of the projects this constraint applies to, `*` matches all projects and a
+    /// trailing `*` matches any suffix
+    pub source_tag: String,
+    /// if present, projects may only depend on projects that have one of these tags
+    #[serde(default)]
+    pub only_depend_on_libs_with_tags: Option<Vec<String>>,
+    /// projects may not depend on projects that have any of these tags
+    #[serde(default)]
+    pub not_depend_on_libs_with_tags: Vec<String>,
+    /// npm packages that projects may not declare, a trailing `*` matches any suffix
+    #[serde(default)]
+    pub banned_packages: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct LicensesConfig {
+    /// the policies that the packages of each project have to satisfy
+    #[serde(default)]
+    pub policies: Vec<LicensePolicy>,
+}
+
+/// A rule that restricts the licenses of the packages installed for projects with a given
+/// tag or type. A policy without `sourceTag` and `projectType` applies to all projects.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct LicensePolicy {
+    /// the tag of the projects this policy applies to, `*` matches all projects and a
+    /// trailing `*` matches any suffix
+    #[serde(default)]
+    pub source_tag: Option<String>,
+    /// the type of the projects this policy applies to, eg: `application`
+    #[serde(default)]
+    pub project_type: Option<String>,
+    /// if present, packages may only be licensed under one of these licenses
+    #[serde(default)]
+    pub allow: Option<Vec<String>>,
+    /// packages may not be licensed under any of these licenses
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// npm packages that are not checked, a trailing `*` matches any suffix
+    #[serde(default)]
+    pub ignore_packages: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(config: &str) -> Result<(), String> {
+        serde_json::from_str::<EsteemConfig>(config)
+            .map_err(|err| err.to_string())?
+            .validate()
+            .map_err(|err| err.0)
+    }
+
+    #[test]
+    fn accepts_the_default_configuration() {
+        assert!(validate("{}").is_ok());
+        assert!(EsteemConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn accepts_known_package_managers() {
+        assert!(validate(r#"{"packageManager": "pnpm"}"#).is_ok());
+        let config = r#"{
+            "packageManager": "bun",
+            "managers": {"bun": {"extends": "npm", "add": "bun add {dev} {packages} {args}"}}
+        }"#;
+        assert!(validate(config).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_package_managers() {
+        let err = validate(r#"{"packageManager": "bun"}"#).unwrap_err();
+        assert!(err.contains("Unknown package manager \"bun\""), "{err}");
+    }
+
+    #[test]
+    fn rejects_invalid_managers() {
+        let err = validate(r#"{"managers": {"bun": {"extends": "bun"}}}"#).unwrap_err();
+        assert!(err.contains("extends \"bun\""), "{err}");
+        let err = validate(r#"{"managers": {"bun": {"add": "bun add {packages}"}}}"#)
+            .unwrap_err();
+        assert!(err.contains("Package manager \"bun\""), "{err}");
+        let config =
+            r#"{"managers": {"bun": {"extends": "npm", "add": "bun add {pkgs}"}}}"#;
+        let err = validate(config).unwrap_err();
+        assert!(
+            err.contains("unknown placeholder \"{pkgs}\" in `add`"),
+            "{err}"
+        );
+        let config = r#"{"managers": {"bun": {"extends": "npm", "exec": "bun 'x"}}}"#;
+        assert!(validate(config).unwrap_err().contains("in `exec`"));
+    }
+
+    #[test]
+    fn rejects_an_empty_backup_file() {
+        let err = validate(r#"{"backupFile": ""}"#).unwrap_err();
+        assert!(err.contains("`backupFile`"), "{err}");
+    }
+
+    #[test]
+    fn requires_the_path_of_the_file_graph_provider() {
+        let err = validate(r#"{"graph": {"provider": "file"}}"#).unwrap_err();
+        assert!(err.contains("`graph.path`"), "{err}");
+        assert!(
+            validate(r#"{"graph": {"provider": "file", "path": "graph.json"}}"#).is_ok()
+        );
+    }
+
+    #[test]
+    fn requires_allow_or_deny_in_license_policies() {
+        let err =
+            validate(r#"{"licenses": {"policies": [{"sourceTag": "*"}]}}"#).unwrap_err();
+        assert!(err.contains("`allow` or `deny`"), "{err}");
+        assert!(validate(r#"{"licenses": {"policies": [{"deny": ["GPL-*"]}]}}"#).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        assert!(validate(r#"{"packageManagers": "pnpm"}"#).is_err());
+        assert!(validate(r#"{"commands": {"add": {"exactly": true}}}"#).is_err());
+    }
+}
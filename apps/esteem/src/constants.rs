@@ -1,11 +1,46 @@
 use std::{fs::canonicalize, path::PathBuf};
 
 pub(crate) const WORKSPACE_FILE: &str = "workspace.json";
+pub(crate) const CONFIG_FILE: &str = "esteem.json";
+pub(crate) const CONFIG_TOML_FILE: &str = "esteem.toml";
+pub(crate) const NX_FILE: &str = "nx.json";
+pub(crate) const CACHE_DIRECTORY: &str = ".esteem/cache";
+pub(crate) const GRAPH_FILE: &str = "graph.json";
 pub(crate) const PACKAGE_JSON_BACKUP_FILE: &str = "package.backup.json";
 pub(crate) const PACKAGE_JSON_FILE: &str = "package.json";
 pub(crate) const PROJECT_FILE: &str = "project.json";
+pub(crate) const NODE_MODULES_DIRECTORY: &str = "node_modules";
+/// The configuration of yarn 2+, which yarn 1 does not read
+pub(crate) const YARNRC_FILE: &str = ".yarnrc.yml";
+pub(crate) const DOCKERFILE: &str = "Dockerfile";
+// BuildKit reads the ignore file next to the `Dockerfile` instead of the one at the root
+pub(crate) const DOCKERIGNORE_FILE: &str = "Dockerfile.dockerignore";
 pub(crate) const REQUIRED_KEY: &str = "required";
 pub(crate) const DEVELOPMENT_KEY: &str = "development";
+pub(crate) const PEER_KEY: &str = "peer";
+pub(crate) const OPTIONAL_KEY: &str = "optional";
+pub(crate) const TARGETS_KEY: &str = "targets";
+pub(crate) const PACKAGE_JSON_DEPENDENCIES_KEY: &str = "dependencies";
+pub(crate) const PACKAGE_JSON_DEV_DEPENDENCIES_KEY: &str = "devDependencies";
+pub(crate) const PACKAGE_JSON_PEER_DEPENDENCIES_KEY: &str = "peerDependencies";
+pub(crate) const PACKAGE_JSON_OPTIONAL_DEPENDENCIES_KEY: &str = "optionalDependencies";
+/// The version of internal packages that are not declared in the root `package.json`
+pub(crate) const WORKSPACE_PROTOCOL_VERSION: &str = "workspace:*";
+
+/// The files at the root of the workspace that can change the project graph
+pub(crate) const WORKSPACE_ROOT_FILES: [&str; 7] = [
+    WORKSPACE_FILE,
+    PACKAGE_JSON_FILE,
+    NX_FILE,
+    CONFIG_FILE,
+    CONFIG_TOML_FILE,
+    "tsconfig.base.json",
+    "tsconfig.json",
+];
+
+/// The lockfiles of the supported package managers
+pub(crate) const LOCKFILES: [&str; 3] =
+    ["package-lock.json", "pnpm-lock.yaml", "yarn.lock"];
 
 pub fn workspace_file() -> PathBuf {
     canonicalize(WORKSPACE_FILE).unwrap()
@@ -1,10 +1,86 @@
 use super::{
-    constants::{DEVELOPMENT_KEY, REQUIRED_KEY},
-    AddEsteemDevelopmentDependency, AddEsteemRequiredDependency, LibraryError,
-    RemoveEsteemDevelopmentDependency, RemoveEsteemRequiredDependency,
+    constants::{
+        DEVELOPMENT_KEY, OPTIONAL_KEY, PACKAGE_JSON_DEPENDENCIES_KEY,
+        PACKAGE_JSON_DEV_DEPENDENCIES_KEY, PACKAGE_JSON_OPTIONAL_DEPENDENCIES_KEY,
+        PACKAGE_JSON_PEER_DEPENDENCIES_KEY, PEER_KEY, REQUIRED_KEY, TARGETS_KEY,
+    },
+    AddEsteemDevelopmentDependency, AddEsteemOptionalDependency, AddEsteemPeerDependency,
+    AddEsteemRequiredDependency, AddEsteemTargetDependency, LibraryError,
+    RemoveEsteemDevelopmentDependency, RemoveEsteemOptionalDependency,
+    RemoveEsteemPeerDependency, RemoveEsteemRequiredDependency,
+    RemoveEsteemTargetDependency,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    str::FromStr,
+};
+
+/// The requirement scopes that a dependency can be declared in
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum RequirementScope {
+    /// dependencies needed at runtime
+    Required,
+    /// dependencies only needed to develop, build or test
+    Development,
+    /// dependencies that the consumers of the project have to provide
+    Peer,
+    /// dependencies that the project can work without
+    Optional,
+}
+
+impl RequirementScope {
+    pub const VARIANTS: [&'static str; 4] =
+        [REQUIRED_KEY, DEVELOPMENT_KEY, PEER_KEY, OPTIONAL_KEY];
+
+    /// every requirement scope, which is what is selected by default
+    pub fn all() -> BTreeSet<Self> {
+        BTreeSet::from([
+            Self::Required,
+            Self::Development,
+            Self::Peer,
+            Self::Optional,
+        ])
+    }
+
+    /// the key of `package.json` that holds the dependencies of this scope
+    pub(crate) fn package_json_key(&self) -> &'static str {
+        match self {
+            Self::Required => PACKAGE_JSON_DEPENDENCIES_KEY,
+            Self::Development => PACKAGE_JSON_DEV_DEPENDENCIES_KEY,
+            Self::Peer => PACKAGE_JSON_PEER_DEPENDENCIES_KEY,
+            Self::Optional => PACKAGE_JSON_OPTIONAL_DEPENDENCIES_KEY,
+        }
+    }
+}
+
+impl fmt::Display for RequirementScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = match self {
+            Self::Required => REQUIRED_KEY,
+            Self::Development => DEVELOPMENT_KEY,
+            Self::Peer => PEER_KEY,
+            Self::Optional => OPTIONAL_KEY,
+        };
+        write!(f, "{key}")
+    }
+}
+
+impl FromStr for RequirementScope {
+    type Err = LibraryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            REQUIRED_KEY => Ok(Self::Required),
+            DEVELOPMENT_KEY => Ok(Self::Development),
+            PEER_KEY => Ok(Self::Peer),
+            OPTIONAL_KEY => Ok(Self::Optional),
+            _ => Err(LibraryError(format!("Unknown requirement scope: {s:?}"))),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EsteemDependencies {
@@ -14,6 +90,18 @@ pub struct EsteemDependencies {
     /// devDependencies of the project/workspace
     #[serde(default)]
     pub development: BTreeSet<String>,
+    /// peerDependencies of the project/workspace
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub peer: BTreeSet<String>,
+    /// optionalDependencies of the project/workspace
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub optional: BTreeSet<String>,
+    /// devDependencies only needed to run some targets, keyed by the name of the target
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub targets: BTreeMap<String, BTreeSet<String>>,
+    /// the names of the dependency groups of the workspace whose dependencies are included
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub groups: BTreeSet<String>,
 }
 
 impl EsteemDependencies {
@@ -22,18 +110,65 @@ impl EsteemDependencies {
             self.development
                 .iter()
                 .chain(self.required.iter())
+                .chain(self.peer.iter())
+                .chain(self.optional.iter())
+                .chain(self.targets.values().flatten())
                 .map(String::from),
         )
     }
+
+    /// the dependencies declared for the `targets` given, or for all targets if none are
+    /// given
+    pub(crate) fn get_target_dependencies<'a>(
+        &'a self,
+        targets: Option<&'a BTreeSet<String>>,
+    ) -> impl Iterator<Item = &'a String> {
+        self.targets
+            .iter()
+            .filter(move |(target, _)| targets.is_none_or(|t| t.contains(*target)))
+            .flat_map(|(_, dependencies)| dependencies)
+    }
+
+    /// adds all dependencies of `other` to these ones, except for its groups
+    pub(crate) fn extend(&mut self, other: &Self) {
+        self.required.extend(other.required.iter().cloned());
+        self.development.extend(other.development.iter().cloned());
+        self.peer.extend(other.peer.iter().cloned());
+        self.optional.extend(other.optional.iter().cloned());
+        for (target, dependencies) in &other.targets {
+            self.targets
+                .entry(target.to_owned())
+                .or_default()
+                .extend(dependencies.iter().cloned());
+        }
+    }
+
+    /// the dependencies declared in a requirement scope
+    pub(crate) fn get_scope(&self, scope: RequirementScope) -> &BTreeSet<String> {
+        match scope {
+            RequirementScope::Required => &self.required,
+            RequirementScope::Development => &self.development,
+            RequirementScope::Peer => &self.peer,
+            RequirementScope::Optional => &self.optional,
+        }
+    }
 }
 
 impl Default for EsteemDependencies {
     fn default() -> Self {
         let required = BTreeSet::new();
         let development = BTreeSet::new();
+        let peer = BTreeSet::new();
+        let optional = BTreeSet::new();
+        let targets = BTreeMap::new();
+        let groups = BTreeSet::new();
         Self {
             required,
             development,
+            peer,
+            optional,
+            targets,
+            groups,
         }
     }
 }
@@ -52,6 +187,27 @@ impl AddEsteemDevelopmentDependency for EsteemDependencies {
     }
 }
 
+impl AddEsteemPeerDependency for EsteemDependencies {
+    fn add_peer_dependency(&mut self, dependency: String) {
+        info!("Adding {PEER_KEY} dependency {dependency:?}");
+        self.peer.insert(dependency);
+    }
+}
+
+impl AddEsteemOptionalDependency for EsteemDependencies {
+    fn add_optional_dependency(&mut self, dependency: String) {
+        info!("Adding {OPTIONAL_KEY} dependency {dependency:?}");
+        self.optional.insert(dependency);
+    }
+}
+
+impl AddEsteemTargetDependency for EsteemDependencies {
+    fn add_target_dependency(&mut self, target: String, dependency: String) {
+        info!("Adding dependency {dependency:?} of target {target:?}");
+        self.targets.entry(target).or_default().insert(dependency);
+    }
+}
+
 impl RemoveEsteemRequiredDependency for EsteemDependencies {
     fn remove_required_dependency(
         &mut self,
@@ -89,3 +245,64 @@ impl RemoveEsteemDevelopmentDependency for EsteemDependencies {
             })
     }
 }
+
+impl RemoveEsteemPeerDependency for EsteemDependencies {
+    fn remove_peer_dependency(&mut self, dependency: String) -> Result<(), LibraryError> {
+        info!("Trying to remove {PEER_KEY} dependency {dependency:?}");
+        self.peer
+            .take(&dependency)
+            .map(|_| {
+                info!(
+                    "Found and removed {PEER_KEY} dependency {dependency:?} successfully"
+                );
+            })
+            .ok_or_else(|| {
+                LibraryError(format!(
+                    "Could not find {PEER_KEY} dependency to remove: {dependency:?}"
+                ))
+            })
+    }
+}
+
+impl RemoveEsteemOptionalDependency for EsteemDependencies {
+    fn remove_optional_dependency(
+        &mut self,
+        dependency: String,
+    ) -> Result<(), LibraryError> {
+        info!("Trying to remove {OPTIONAL_KEY} dependency {dependency:?}");
+        self.optional
+            .take(&dependency)
+            .map(|_| {
+                info!("Found and removed {OPTIONAL_KEY} dependency {dependency:?} successfully");
+            })
+            .ok_or_else(|| {
+                LibraryError(format!(
+                    "Could not find {OPTIONAL_KEY} dependency to remove: {dependency:?}"
+                ))
+            })
+    }
+}
+
+impl RemoveEsteemTargetDependency for EsteemDependencies {
+    fn remove_target_dependency(
+        &mut self,
+        dependency: String,
+    ) -> Result<(), LibraryError> {
+        info!("Trying to remove {TARGETS_KEY} dependency {dependency:?}");
+        let mut removed = false;
+        for (target, dependencies) in self.targets.iter_mut() {
+            if dependencies.remove(&dependency) {
+                info!("Found and removed dependency {dependency:?} of target {target:?} successfully");
+                removed = true;
+            }
+        }
+        self.targets
+            .retain(|_, dependencies| !dependencies.is_empty());
+        match removed {
+            true => Ok(()),
+            false => Err(LibraryError(format!(
+                "Could not find {TARGETS_KEY} dependency to remove: {dependency:?}"
+            ))),
+        }
+    }
+}
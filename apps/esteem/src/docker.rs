@@ -0,0 +1,199 @@
+use super::{
+    constants::{
+        CONFIG_FILE, CONFIG_TOML_FILE, LOCKFILES, PACKAGE_JSON_FILE, PROJECT_FILE,
+        WORKSPACE_FILE, WORKSPACE_ROOT_FILES,
+    },
+    managers::PackageManager,
+    LibraryError,
+};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The image that every stage is based on when none is configured
+pub const DEFAULT_BASE_IMAGE: &str = "node:18";
+
+/// The command that installs esteem in the pruner stage, pinned to this version since the
+/// `Dockerfile` relies on its options and configuration
+const INSTALL_ESTEEM: &str = concat!(
+    "curl -fsSL https://raw.githubusercontent.com/IgnisDa/developrs/esteem-v",
+    env!("CARGO_PKG_VERSION"),
+    "/apps/esteem/install.sh | bash -s -- --yes --version ",
+    env!("CARGO_PKG_VERSION")
+);
+
+/// The directory the workspace is copied to in every stage
+const WORKDIR: &str = "/workspace";
+
+/// Everything needed to write the `Dockerfile` (and its `.dockerignore`) of a project
+#[derive(Debug)]
+pub struct DockerContext {
+    project_name: String,
+    base_image: String,
+    /// the roots of the project and all the projects needed to isolate it
+    roots: BTreeMap<String, PathBuf>,
+    /// the roots of the other projects of the workspace, which only get an empty
+    /// `project.json` so that the workspace can be read
+    stubs: Vec<PathBuf>,
+    /// whether any of the projects is an internal package, which is left out of the
+    /// pruned `package.json` since its sources are copied to the builder instead
+    has_internal_packages: bool,
+    install_command: String,
+    build_command: String,
+}
+
+impl DockerContext {
+    /// creates the context of `project_name` from its own root and the ones of all the
+    /// projects needed to isolate it
+    pub fn new(
+        project_name: &str,
+        roots: BTreeMap<String, PathBuf>,
+        stubs: Vec<PathBuf>,
+        base_image: String,
+        has_internal_packages: bool,
+    ) -> Result<Self, LibraryError> {
+        // the lockfile is out of sync with the pruned `package.json`, so the install
+        // can not be frozen
+        let mut manager = PackageManager::get_command_executor(true)?;
+        manager.install_dependencies(false, false, vec![])?;
+        let install_command = manager.command_line();
+        let mut manager = PackageManager::get_command_executor(true)?;
+        manager.run_binary(vec!["nx".into(), "build".into(), project_name.to_owned()])?;
+        let build_command = manager.command_line();
+        Ok(Self {
+            project_name: project_name.to_owned(),
+            base_image,
+            roots,
+            stubs,
+            has_internal_packages,
+            install_command,
+            build_command,
+        })
+    }
+
+    /// returns the root of the project the context was created for
+    pub fn get_project_root(&self) -> &Path {
+        &self.roots[&self.project_name]
+    }
+
+    pub fn render_dockerfile(&self, dockerfile: &Path) -> String {
+        let project_names = self.roots.keys().cloned().collect::<Vec<_>>();
+        let pruner_files = existing_files(&[
+            PACKAGE_JSON_FILE,
+            WORKSPACE_FILE,
+            CONFIG_FILE,
+            CONFIG_TOML_FILE,
+        ]);
+        let builder_files = existing_files(&WORKSPACE_ROOT_FILES)
+            .into_iter()
+            .filter(|f| ![PACKAGE_JSON_FILE, CONFIG_FILE, CONFIG_TOML_FILE].contains(f))
+            .collect::<Vec<_>>();
+        let lockfiles = existing_files(&LOCKFILES);
+
+        let mut ret = format!(
+            "# Generated by esteem for {:?}, build it from the workspace root with:\n# docker build -f {} .\n",
+            self.project_name,
+            dockerfile.display()
+        );
+
+        // only the manifests are copied, so this stage is cached until one of them changes
+        ret.push_str(&self.stage("pruner"));
+        ret.push_str(&format!("RUN {INSTALL_ESTEEM}\n"));
+        ret.push_str(&format!("COPY {} ./\n", pruner_files.join(" ")));
+        for root in self.roots.values() {
+            // the `package.json` of a project makes it an internal package
+            for file in [PROJECT_FILE, PACKAGE_JSON_FILE] {
+                let path = root.join(file);
+                if path.exists() {
+                    ret.push_str(&format!("COPY {0} {0}\n", path.display()));
+                }
+            }
+        }
+        ret.push_str(&self.create_stubs());
+        let workspace_protocol = match self.has_internal_packages {
+            true => " --workspace-protocol strip",
+            false => "",
+        };
+        ret.push_str(&format!(
+            "RUN esteem install-isolated {} --without-graph{workspace_protocol}\n",
+            project_names.join(" ")
+        ));
+
+        ret.push_str(&self.stage("installer"));
+        if !lockfiles.is_empty() {
+            ret.push_str(&format!("COPY {} ./\n", lockfiles.join(" ")));
+        }
+        ret.push_str(&format!(
+            "COPY --from=pruner {WORKDIR}/{PACKAGE_JSON_FILE} ./\n"
+        ));
+        ret.push_str(&format!(
+            "RUN corepack enable && {}\n",
+            self.install_command
+        ));
+
+        ret.push_str(&self.stage("builder"));
+        ret.push_str(&format!(
+            "COPY --from=installer {WORKDIR}/node_modules node_modules\n"
+        ));
+        ret.push_str(&format!(
+            "COPY --from=pruner {WORKDIR}/{PACKAGE_JSON_FILE} ./\n"
+        ));
+        if !builder_files.is_empty() {
+            ret.push_str(&format!("COPY {} ./\n", builder_files.join(" ")));
+        }
+        for root in self.roots.values() {
+            ret.push_str(&format!("COPY {0} {0}\n", root.display()));
+        }
+        ret.push_str(&self.create_stubs());
+        ret.push_str(&format!("RUN corepack enable && {}\n", self.build_command));
+        ret
+    }
+
+    /// the instruction that creates the empty `project.json` of the projects that are not
+    /// copied, since NX can not read the workspace without them
+    fn create_stubs(&self) -> String {
+        if self.stubs.is_empty() {
+            return String::new();
+        }
+        let directories = self
+            .stubs
+            .iter()
+            .map(|root| root.to_string_lossy())
+            .collect::<Vec<_>>();
+        format!(
+            "RUN for root in {}; do mkdir -p \"$root\" && echo '{{}}' > \"$root/{PROJECT_FILE}\"; done\n",
+            shell_words::join(directories)
+        )
+    }
+
+    /// the `.dockerignore` that excludes everything except the files the `Dockerfile`
+    /// copies
+    pub fn render_dockerignore(&self) -> String {
+        let mut ret = format!("# Generated by esteem for {:?}\n*\n", self.project_name);
+        for file in existing_files(&WORKSPACE_ROOT_FILES)
+            .into_iter()
+            .chain(existing_files(&LOCKFILES))
+        {
+            ret.push_str(&format!("!{file}\n"));
+        }
+        for root in self.roots.values() {
+            ret.push_str(&format!("!{}\n", root.display()));
+        }
+        ret.push_str("**/node_modules\n");
+        ret
+    }
+
+    fn stage(&self, name: &str) -> String {
+        format!("\nFROM {} AS {name}\nWORKDIR {WORKDIR}\n", self.base_image)
+    }
+}
+
+/// returns the `files` that exist at the root of the workspace
+fn existing_files<'a>(files: &[&'a str]) -> Vec<&'a str> {
+    files
+        .iter()
+        .filter(|file| Path::new(file).exists())
+        .copied()
+        .collect()
+}
@@ -0,0 +1,176 @@
+use super::{
+    dependencies::RequirementScope, graph::NxProject, workspace::EsteemWorkspace,
+    LibraryError,
+};
+use serde::{Deserialize, Serialize, Serializer};
+use std::{collections::BTreeSet, str::FromStr};
+
+/// The formats that the project graph can be exported to
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+impl GraphFormat {
+    pub const VARIANTS: [&'static str; 3] = ["dot", "mermaid", "json"];
+}
+
+impl FromStr for GraphFormat {
+    type Err = LibraryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "mermaid" => Ok(Self::Mermaid),
+            "json" => Ok(Self::Json),
+            _ => Err(LibraryError(format!("Unknown graph format: {s:?}"))),
+        }
+    }
+}
+
+/// A part of the project graph that can be rendered in one of the [`GraphFormat`]s
+#[derive(Debug, Serialize)]
+pub struct GraphExport {
+    nodes: Vec<ExportNode>,
+    edges: Vec<ExportEdge>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportNode {
+    name: String,
+    root: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dependencies: Option<DependencyCounts>,
+}
+
+/// The number of npm packages a project declares in each requirement scope, serialized
+/// as an object keyed by the scope
+#[derive(Debug)]
+struct DependencyCounts(Vec<(RequirementScope, usize)>);
+
+impl Serialize for DependencyCounts {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.0
+                .iter()
+                .map(|(scope, count)| (scope.to_string(), count)),
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ExportEdge {
+    source: String,
+    target: String,
+}
+
+impl GraphExport {
+    /// creates an export containing `projects` and the edges between them
+    pub fn new(graph: &NxProject, projects: &BTreeSet<String>) -> Self {
+        let nodes = projects
+            .iter()
+            .map(|name| ExportNode {
+                name: name.to_owned(),
+                root: graph.get_project_root(name).unwrap_or_default().to_owned(),
+                dependencies: None,
+            })
+            .collect();
+        let edges = graph
+            .get_edges()
+            .into_iter()
+            .filter(|(s, t)| projects.contains(s) && projects.contains(t))
+            .map(|(source, target)| ExportEdge { source, target })
+            .collect();
+        Self { nodes, edges }
+    }
+
+    /// annotates each project with the number of npm packages it declares
+    pub fn annotate(&mut self, workspace: &EsteemWorkspace) {
+        for node in self.nodes.iter_mut() {
+            if let Ok(project) = workspace.get_project(node.name.clone()) {
+                let dependencies = workspace.resolve(&project.dependencies);
+                node.dependencies = Some(DependencyCounts(
+                    RequirementScope::all()
+                        .into_iter()
+                        .map(|scope| (scope, dependencies.get_scope(scope).len()))
+                        .collect(),
+                ));
+            }
+        }
+    }
+
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+            GraphFormat::Json => serde_json::to_string_pretty(self).unwrap(),
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut ret = String::from("digraph {\n");
+        for node in self.nodes.iter() {
+            ret.push_str(&format!(
+                "    \"{}\" [label=\"{}\"];\n",
+                dot_escape(&node.name),
+                node.label("\\n", dot_escape)
+            ));
+        }
+        for edge in self.edges.iter() {
+            ret.push_str(&format!(
+                "    \"{}\" -> \"{}\";\n",
+                dot_escape(&edge.source),
+                dot_escape(&edge.target)
+            ));
+        }
+        ret.push_str("}\n");
+        ret
+    }
+
+    fn to_mermaid(&self) -> String {
+        // mermaid identifiers can not contain most special characters, so the position
+        // of the node is used as its identifier instead of the project name
+        let id = |name: &str| {
+            let index = self.nodes.iter().position(|n| n.name == name).unwrap();
+            format!("n{index}")
+        };
+        let mut ret = String::from("graph TD\n");
+        for node in self.nodes.iter() {
+            let label = node.label("<br/>", |name| name.replace('"', "#quot;"));
+            ret.push_str(&format!("    {}[\"{}\"]\n", id(&node.name), label));
+        }
+        for edge in self.edges.iter() {
+            ret.push_str(&format!(
+                "    {} --> {}\n",
+                id(&edge.source),
+                id(&edge.target)
+            ));
+        }
+        ret
+    }
+}
+
+/// escapes `value` to be used in a double-quoted DOT string
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl ExportNode {
+    /// the label of the node, with its name escaped by `escape`
+    fn label(&self, line_break: &str, escape: fn(&str) -> String) -> String {
+        let name = escape(&self.name);
+        match &self.dependencies {
+            Some(DependencyCounts(counts)) => {
+                let counts = counts
+                    .iter()
+                    .map(|(scope, count)| format!("{scope}: {count}"))
+                    .collect::<Vec<_>>();
+                format!("{name}{line_break}{}", counts.join(", "))
+            }
+            None => name,
+        }
+    }
+}
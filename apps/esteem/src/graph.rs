@@ -1,7 +1,40 @@
 use super::{constants::PROJECT_FILE, LibraryError};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::{collections::HashMap, fs::read_to_string, path::PathBuf};
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The direction in which edges of the project graph should be followed
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// the projects that a project depends on (directly or transitively)
+    Upstream,
+    /// the projects that depend on a project (directly or transitively)
+    Downstream,
+    /// the union of upstream and downstream projects
+    Both,
+}
+
+impl Direction {
+    pub const VARIANTS: [&'static str; 3] = ["upstream", "downstream", "both"];
+}
+
+impl FromStr for Direction {
+    type Err = LibraryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "upstream" => Ok(Self::Upstream),
+            "downstream" => Ok(Self::Downstream),
+            "both" => Ok(Self::Both),
+            _ => Err(LibraryError(format!("Unknown direction: {s:?}"))),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct NxProject {
@@ -10,31 +43,237 @@ pub struct NxProject {
 }
 
 impl NxProject {
-    pub fn from_path(path: PathBuf) -> Result<Self, LibraryError> {
-        let workspace_file = read_to_string(&path).unwrap();
-        Ok(serde_json::from_str(&workspace_file).unwrap())
+    pub fn from_path(path: &Path) -> Result<Self, LibraryError> {
+        let graph_file = read_to_string(path)
+            .map_err(|err| LibraryError(format!("Could not read {path:?}: {err}")))?;
+        serde_json::from_str(&graph_file)
+            .map_err(|err| LibraryError(format!("Could not parse {path:?}: {err}")))
     }
 
-    pub fn get_project_dependencies(&self) -> Vec<String> {
-        self.graph.dependencies.keys().cloned().collect()
+    /// returns the names of all projects connected to `project_name` in the given
+    /// direction, including `project_name` itself. Fails if `project_name` is not a part
+    /// of the graph.
+    pub fn get_project_dependencies(
+        &self,
+        project_name: &str,
+        direction: Direction,
+    ) -> Result<BTreeSet<String>, LibraryError> {
+        if !self.graph.nodes.contains_key(project_name) {
+            return Err(LibraryError(format!(
+                "{project_name:?} is not a part of the project graph"
+            )));
+        }
+        let mut ret = BTreeSet::from([project_name.to_owned()]);
+        if matches!(direction, Direction::Upstream | Direction::Both) {
+            ret.extend(self.traverse(project_name, |p| self.direct_upstream(p)));
+        }
+        if matches!(direction, Direction::Downstream | Direction::Both) {
+            ret.extend(self.traverse(project_name, |p| self.direct_downstream(p)));
+        }
+        Ok(ret)
     }
 
-    pub fn get_projects_with_config_path(&self) -> HashMap<String, PathBuf> {
+    /// returns the union of [`Self::get_project_dependencies`] for all the given projects
+    pub fn get_projects_dependencies(
+        &self,
+        project_names: &[String],
+        direction: Direction,
+    ) -> Result<BTreeSet<String>, LibraryError> {
+        let mut ret = BTreeSet::new();
+        for name in project_names {
+            ret.extend(self.get_project_dependencies(name, direction)?);
+        }
+        Ok(ret)
+    }
+
+    pub fn get_projects_with_config_path(
+        &self,
+        project_name: &str,
+        direction: Direction,
+    ) -> Result<HashMap<String, PathBuf>, LibraryError> {
         let mut ret = HashMap::new();
-        for proj in self.get_project_dependencies() {
+        for proj in self.get_project_dependencies(project_name, direction)? {
             let path = self.graph.nodes.get(&proj).unwrap();
             ret.insert(proj, PathBuf::from(&path.data.root).join(PROJECT_FILE));
         }
-        ret
+        Ok(ret)
+    }
+
+    /// returns the names of the projects that `project_name` directly depends on
+    pub fn get_direct_dependencies(&self, project_name: &str) -> BTreeSet<String> {
+        self.direct_upstream(project_name)
+            .filter(|target| *target != project_name)
+            .map(String::from)
+            .collect()
+    }
+
+    /// returns the names of all projects present in the graph
+    pub fn get_project_names(&self) -> BTreeSet<String> {
+        self.graph.nodes.keys().cloned().collect()
+    }
+
+    /// returns the root directory of a project in the graph
+    pub fn get_project_root(&self, project_name: &str) -> Option<&str> {
+        self.graph
+            .nodes
+            .get(project_name)
+            .map(|n| n.data.root.as_str())
+    }
+
+    /// returns all `(source, target)` pairs where the project `source` depends on the
+    /// project `target`
+    pub fn get_edges(&self) -> BTreeSet<(String, String)> {
+        self.graph
+            .dependencies
+            .keys()
+            .flat_map(|source| {
+                self.direct_upstream(source)
+                    .map(move |target| (source.to_owned(), target.to_owned()))
+            })
+            .collect()
+    }
+
+    /// returns the strongly connected components of the project graph that contain a
+    /// cycle, each as a path that starts and ends at the same project
+    pub fn get_cycles(&self) -> Vec<Vec<String>> {
+        let mut state = TarjanState::default();
+        for name in self.get_project_names() {
+            if !state.indices.contains_key(&name) {
+                self.strong_connect(&name, &mut state);
+            }
+        }
+        state
+            .components
+            .into_iter()
+            .filter(|c| {
+                c.len() > 1 || self.direct_upstream(&c[0]).any(|t| t == c[0].as_str())
+            })
+            .map(|c| self.cycle_path(&c.into_iter().collect()))
+            .collect()
+    }
+
+    /// one step of Tarjan's strongly connected components algorithm
+    fn strong_connect(&self, name: &str, state: &mut TarjanState) {
+        let index = state.indices.len();
+        state.indices.insert(name.to_owned(), index);
+        state.low_links.insert(name.to_owned(), index);
+        state.stack.push(name.to_owned());
+        for target in self.direct_upstream(name) {
+            if !state.indices.contains_key(target) {
+                self.strong_connect(target, state);
+                let low_link = state.low_links[name].min(state.low_links[target]);
+                state.low_links.insert(name.to_owned(), low_link);
+            } else if state.stack.iter().any(|s| s == target) {
+                let low_link = state.low_links[name].min(state.indices[target]);
+                state.low_links.insert(name.to_owned(), low_link);
+            }
+        }
+        if state.low_links[name] == state.indices[name] {
+            let mut component = vec![];
+            while let Some(member) = state.stack.pop() {
+                let is_root = member == name;
+                component.push(member);
+                if is_root {
+                    break;
+                }
+            }
+            component.sort();
+            state.components.push(component);
+        }
+    }
+
+    /// finds a path through the members of a strongly connected component that starts
+    /// and ends at its (alphabetically) first member
+    fn cycle_path(&self, component: &BTreeSet<String>) -> Vec<String> {
+        let start = component.iter().next().unwrap().as_str();
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(current) = queue.pop_front() {
+            for target in self.direct_upstream(current) {
+                if !component.contains(target) {
+                    continue;
+                }
+                if target == start {
+                    let mut path = vec![start.to_owned()];
+                    let mut step = current;
+                    while step != start {
+                        path.push(step.to_owned());
+                        step = previous[step];
+                    }
+                    path.push(start.to_owned());
+                    path.reverse();
+                    return path;
+                }
+                if !previous.contains_key(target) {
+                    previous.insert(target, current);
+                    queue.push_back(target);
+                }
+            }
+        }
+        unreachable!("every strongly connected component with an edge contains a cycle")
+    }
+
+    /// collects every project reachable from `start` by repeatedly calling `next`
+    fn traverse<'a, F, I>(&'a self, start: &'a str, next: F) -> BTreeSet<String>
+    where
+        F: Fn(&'a str) -> I,
+        I: Iterator<Item = &'a str>,
+    {
+        let mut visited = BTreeSet::new();
+        let mut stack = vec![start];
+        while let Some(current) = stack.pop() {
+            for neighbour in next(current) {
+                if visited.insert(neighbour.to_owned()) {
+                    stack.push(neighbour);
+                }
+            }
+        }
+        visited.remove(start);
+        visited
+    }
+
+    /// the projects that `project_name` directly depends on
+    fn direct_upstream<'a>(
+        &'a self,
+        project_name: &str,
+    ) -> impl Iterator<Item = &'a str> {
+        self.graph
+            .dependencies
+            .get(project_name)
+            .into_iter()
+            .flatten()
+            .map(|d| d.target.as_str())
+            .filter(|t| self.graph.nodes.contains_key(*t))
+    }
+
+    /// the projects that directly depend on `project_name`
+    fn direct_downstream<'a>(
+        &'a self,
+        project_name: &'a str,
+    ) -> impl Iterator<Item = &'a str> {
+        self.graph
+            .dependencies
+            .iter()
+            .filter(move |(_, deps)| deps.iter().any(|d| d.target == project_name))
+            .map(|(source, _)| source.as_str())
     }
 }
 
+/// The book-keeping required by [`NxProject::strong_connect`]
+#[derive(Debug, Default)]
+struct TarjanState {
+    indices: HashMap<String, usize>,
+    low_links: HashMap<String, usize>,
+    stack: Vec<String>,
+    components: Vec<Vec<String>>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 struct NxGraph {
     #[serde(default)]
     nodes: HashMap<String, NxNode>,
     #[serde(default)]
-    dependencies: HashMap<String, Value>,
+    dependencies: HashMap<String, Vec<NxDependency>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -48,3 +287,202 @@ struct NxData {
     #[serde(default)]
     root: String,
 }
+
+/// An edge of the project graph, `source` depends on `target`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct NxDependency {
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    target: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// a graph of `nodes` where each edge `(source, target)` means that `source` depends
+    /// on `target`
+    fn graph(nodes: &[&str], edges: &[(&str, &str)]) -> NxProject {
+        let nodes = nodes
+            .iter()
+            .map(|name| {
+                (
+                    name.to_string(),
+                    json!({ "data": { "root": format!("libs/{name}") } }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let mut dependencies = serde_json::Map::new();
+        for (source, target) in edges {
+            dependencies
+                .entry(source.to_string())
+                .or_insert_with(|| json!([]))
+                .as_array_mut()
+                .unwrap()
+                .push(json!({ "source": source, "target": target }));
+        }
+        serde_json::from_value(
+            json!({ "graph": { "nodes": nodes, "dependencies": dependencies } }),
+        )
+        .unwrap()
+    }
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn path(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn dependencies(
+        graph: &NxProject,
+        name: &str,
+        direction: Direction,
+    ) -> BTreeSet<String> {
+        graph.get_project_dependencies(name, direction).unwrap()
+    }
+
+    #[test]
+    fn traverses_a_chain() {
+        let chain = graph(&["a", "b", "c"], &[("a", "b"), ("b", "c")]);
+        assert_eq!(
+            dependencies(&chain, "a", Direction::Upstream),
+            names(&["a", "b", "c"])
+        );
+        assert_eq!(
+            dependencies(&chain, "b", Direction::Upstream),
+            names(&["b", "c"])
+        );
+        assert_eq!(
+            dependencies(&chain, "c", Direction::Upstream),
+            names(&["c"])
+        );
+        assert_eq!(
+            dependencies(&chain, "c", Direction::Downstream),
+            names(&["a", "b", "c"])
+        );
+        assert_eq!(
+            dependencies(&chain, "b", Direction::Downstream),
+            names(&["a", "b"])
+        );
+        assert_eq!(
+            dependencies(&chain, "b", Direction::Both),
+            names(&["a", "b", "c"])
+        );
+        assert!(chain.get_cycles().is_empty());
+    }
+
+    #[test]
+    fn traverses_a_diamond() {
+        let diamond = graph(
+            &["a", "b", "c", "d", "e"],
+            &[
+                ("a", "b"),
+                ("a", "c"),
+                ("b", "d"),
+                ("c", "d"),
+                ("a", "npm:react"),
+            ],
+        );
+        let all = names(&["a", "b", "c", "d"]);
+        assert_eq!(dependencies(&diamond, "a", Direction::Upstream), all);
+        assert_eq!(dependencies(&diamond, "d", Direction::Downstream), all);
+        assert_eq!(
+            dependencies(&diamond, "c", Direction::Both),
+            names(&["a", "c", "d"])
+        );
+        assert_eq!(dependencies(&diamond, "e", Direction::Both), names(&["e"]));
+        assert_eq!(diamond.get_direct_dependencies("a"), names(&["b", "c"]));
+        assert_eq!(
+            diamond
+                .get_projects_dependencies(&path(&["b", "c"]), Direction::Upstream)
+                .unwrap(),
+            names(&["b", "c", "d"])
+        );
+        // the packages of npm are not projects
+        assert!(!diamond
+            .get_edges()
+            .iter()
+            .any(|(_, target)| target == "npm:react"));
+        assert_eq!(diamond.get_edges().len(), 4);
+        assert!(diamond.get_cycles().is_empty());
+    }
+
+    #[test]
+    fn fails_for_unknown_projects() {
+        let chain = graph(&["a", "b"], &[("a", "b")]);
+        assert!(chain
+            .get_project_dependencies("z", Direction::Upstream)
+            .is_err());
+        assert!(chain
+            .get_projects_dependencies(&path(&["a", "z"]), Direction::Both)
+            .is_err());
+        assert!(chain
+            .get_projects_with_config_path("z", Direction::Upstream)
+            .is_err());
+    }
+
+    #[test]
+    fn finds_self_loops() {
+        let looped = graph(&["a", "b"], &[("a", "a"), ("a", "b")]);
+        assert_eq!(looped.get_cycles(), vec![path(&["a", "a"])]);
+        assert_eq!(looped.get_direct_dependencies("a"), names(&["b"]));
+        assert_eq!(
+            dependencies(&looped, "a", Direction::Upstream),
+            names(&["a", "b"])
+        );
+    }
+
+    #[test]
+    fn finds_separate_cycles() {
+        let cycles = graph(
+            &["a", "b", "c", "d", "e", "f"],
+            &[
+                ("a", "b"),
+                ("b", "a"),
+                ("c", "d"),
+                ("d", "e"),
+                ("e", "c"),
+                ("f", "a"),
+                ("e", "f"),
+            ],
+        );
+        let mut found = cycles.get_cycles();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![path(&["a", "b", "a"]), path(&["c", "d", "e", "c"])]
+        );
+        assert_eq!(
+            dependencies(&cycles, "a", Direction::Upstream),
+            names(&["a", "b"])
+        );
+        assert_eq!(
+            dependencies(&cycles, "a", Direction::Downstream),
+            names(&["a", "b", "c", "d", "e", "f"])
+        );
+    }
+
+    #[test]
+    fn finds_the_shortest_cycle_path_of_a_component() {
+        // `a -> b -> a` is shorter than `a -> b -> c -> a`
+        let component = graph(
+            &["a", "b", "c"],
+            &[("a", "b"), ("b", "c"), ("c", "a"), ("b", "a")],
+        );
+        assert_eq!(component.get_cycles(), vec![path(&["a", "b", "a"])]);
+        assert_eq!(
+            component.cycle_path(&names(&["a", "b", "c"])),
+            path(&["a", "b", "a"])
+        );
+        // every member of the component is on a cycle through the first one
+        let ring = graph(
+            &["a", "b", "c", "d"],
+            &[("b", "c"), ("c", "d"), ("d", "a"), ("a", "b")],
+        );
+        assert_eq!(ring.get_cycles(), vec![path(&["a", "b", "c", "d", "a"])]);
+    }
+}
@@ -0,0 +1,118 @@
+use super::project::EsteemProject;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The executor of NX that runs arbitrary commands
+const RUN_COMMANDS_EXECUTOR: &str = "nx:run-commands";
+
+/// The commands that run the binary passed to them
+const BINARY_RUNNERS: [&str; 3] = ["npx", "pnpx", "bunx"];
+
+/// The package managers that run a binary when it is passed after `exec`
+const PACKAGE_MANAGERS: [&str; 3] = ["npm", "pnpm", "yarn"];
+
+/// The prefix of binaries that are called by their path
+const BINARY_DIRECTORY: &str = "node_modules/.bin/";
+
+/// returns the packages that `project` needs to run its targets (or only `targets` if they
+/// are given) without declaring them:
+/// the packages of their executors and the ones providing the binaries that their
+/// `nx:run-commands` command lines call. `binaries` maps the binaries that are known to
+/// the package providing them, a binary that is not known is only matched to a package of
+/// `packages` with the same name (and ignored otherwise, eg: `cargo`). Each package is
+/// mapped to the reason it is needed.
+pub fn get_implicit_dependencies(
+    project: &EsteemProject,
+    targets: Option<&BTreeSet<String>>,
+    binaries: &BTreeMap<String, String>,
+    packages: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let mut ret = BTreeMap::new();
+    for (name, target) in project.get_targets() {
+        if targets.is_some_and(|t| !t.contains(name)) {
+            continue;
+        }
+        let executor = match target.get("executor").and_then(Value::as_str) {
+            Some(executor) => executor,
+            None => continue,
+        };
+        if let Some(package) = get_executor_package(executor) {
+            ret.entry(package.to_owned())
+                .or_insert_with(|| format!("executor {executor:?} of target {name:?}"));
+        }
+        if executor != RUN_COMMANDS_EXECUTOR {
+            continue;
+        }
+        for command in get_commands(target.get("options")) {
+            for binary in get_binaries(command) {
+                let package = binaries
+                    .get(binary)
+                    .map(String::as_str)
+                    .or_else(|| packages.contains_key(binary).then_some(binary));
+                if let Some(package) = package {
+                    ret.entry(package.to_owned()).or_insert_with(|| {
+                        format!("binary {binary:?} of target {name:?}")
+                    });
+                }
+            }
+        }
+    }
+    ret
+}
+
+/// returns the package of an executor like `@nrwl/next:build`, executors of the
+/// workspace (eg: `./tools/executors/deploy:deploy`) do not have one
+fn get_executor_package(executor: &str) -> Option<&str> {
+    let (package, _) = executor.split_once(':')?;
+    match package.starts_with(['.', '/']) || package.is_empty() {
+        true => None,
+        false => Some(package),
+    }
+}
+
+/// returns the command lines of the options of an `nx:run-commands` target, which are
+/// either `command` or `commands` (as strings or objects with a `command`)
+fn get_commands(options: Option<&Value>) -> Vec<&str> {
+    let options = match options {
+        Some(options) => options,
+        None => return vec![],
+    };
+    let commands = options
+        .get("commands")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|command| match command {
+            Value::String(command) => Some(command.as_str()),
+            Value::Object(command) => command.get("command").and_then(Value::as_str),
+            _ => None,
+        });
+    options
+        .get("command")
+        .and_then(Value::as_str)
+        .into_iter()
+        .chain(commands)
+        .collect()
+}
+
+/// returns the binaries that a command line calls, eg: `tsc` and `eslint` for
+/// `NODE_ENV=test npx tsc && pnpm exec eslint .`
+fn get_binaries(command: &str) -> Vec<&str> {
+    command
+        .split(['&', '|', ';', '\n'])
+        .filter_map(|part| {
+            let mut words = part
+                .split_whitespace()
+                // environment variables set for the command
+                .skip_while(|w| w.contains('=') && !w.starts_with('-'));
+            let mut binary = words.next()?;
+            if BINARY_RUNNERS.contains(&binary)
+                || (PACKAGE_MANAGERS.contains(&binary) && words.next() == Some("exec"))
+            {
+                binary = words.find(|w| !w.starts_with('-'))?;
+            }
+            let binary = binary.strip_prefix("./").unwrap_or(binary);
+            Some(binary.strip_prefix(BINARY_DIRECTORY).unwrap_or(binary))
+        })
+        .collect()
+}
@@ -0,0 +1,80 @@
+use super::constants::NODE_MODULES_DIRECTORY;
+use std::{
+    collections::BTreeSet,
+    fs::{read_dir, read_to_string},
+    path::Path,
+};
+
+/// The extensions of the source files that are scanned for imports
+const SOURCE_EXTENSIONS: [&str; 8] =
+    ["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"];
+
+/// The directories that never contain sources of the project
+const IGNORED_DIRECTORIES: [&str; 3] = [NODE_MODULES_DIRECTORY, "dist", "coverage"];
+
+/// The expressions that are followed by the module being imported
+const IMPORT_PREFIXES: [&str; 4] = ["from", "import", "require(", "import("];
+
+/// returns the packages imported (or required) by the source files in `root`, eg:
+/// `lodash` for `import map from 'lodash/map'`. Relative imports and the built-in modules
+/// imported with `node:` are skipped.
+pub fn get_imported_packages(root: &Path) -> BTreeSet<String> {
+    let mut ret = BTreeSet::new();
+    let entries = match read_dir(root) {
+        Ok(entries) => entries,
+        Err(_) => return ret,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !name.starts_with('.') && !IGNORED_DIRECTORIES.contains(&name.as_str()) {
+                ret.extend(get_imported_packages(&path));
+            }
+            continue;
+        }
+        let is_source = path
+            .extension()
+            .is_some_and(|e| SOURCE_EXTENSIONS.contains(&e.to_string_lossy().as_ref()));
+        if let Some(contents) = is_source.then(|| read_to_string(&path).ok()).flatten() {
+            ret.extend(get_imports(&contents).filter_map(get_package_name));
+        }
+    }
+    ret
+}
+
+/// returns the modules that the source imports, in any of the forms `from 'x'`,
+/// `import 'x'`, `require('x')` or `import('x')`
+fn get_imports(contents: &str) -> impl Iterator<Item = &str> {
+    IMPORT_PREFIXES.iter().flat_map(move |prefix| {
+        contents
+            .match_indices(prefix)
+            .filter_map(move |(index, _)| {
+                let rest = contents[index + prefix.len()..].trim_start();
+                let quote = rest
+                    .chars()
+                    .next()
+                    .filter(|c| ['\'', '"', '`'].contains(c))?;
+                let module = &rest[1..];
+                module.find(quote).map(|end| &module[..end])
+            })
+    })
+}
+
+/// returns the package of a module, eg: `@acme/ui` for `@acme/ui/button`
+fn get_package_name(module: &str) -> Option<String> {
+    if module.starts_with(['.', '/']) || module.contains(':') || module.is_empty() {
+        return None;
+    }
+    let segments = match module.starts_with('@') {
+        true => 2,
+        false => 1,
+    };
+    Some(
+        module
+            .splitn(segments + 1, '/')
+            .take(segments)
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
@@ -1,19 +1,48 @@
 use core::fmt;
 use std::{error::Error, fs::write, path::PathBuf};
+mod boundaries;
+mod cache;
+mod config;
 mod constants;
 mod dependencies;
+mod docker;
+mod export;
 mod graph;
+mod implicit;
+mod imports;
+mod licenses;
+mod lockfile;
 mod managers;
+mod manifest;
+mod node_modules;
+mod optimize;
 mod project;
+mod publish;
+mod sbom;
+mod size;
+mod specifier;
 mod utils;
 mod workspace;
 use serde::Serialize;
 pub use {
     cli::{
-        perform_add, perform_init, perform_install_isolated, perform_remove,
-        perform_workspace_add, perform_workspace_remove, utils_get_dependencies,
+        perform_add, perform_boundaries, perform_check, perform_cycles, perform_docker,
+        perform_generate_package_json, perform_graph, perform_init, perform_install,
+        perform_install_isolated, perform_licenses, perform_list, perform_optimize,
+        perform_remove, perform_sbom, perform_size, perform_workspace_add,
+        perform_workspace_remove, utils_get_dependencies, AddOptions,
+        InstallIsolatedOptions,
     },
+    config::EsteemConfig,
+    dependencies::RequirementScope,
+    docker::DEFAULT_BASE_IMAGE,
+    export::GraphFormat,
+    graph::Direction,
+    optimize::{DEFAULT_MAX_USERS, DEFAULT_MIN_SHARE},
+    sbom::SbomFormat,
+    size::{SizeFormat, DEFAULT_TOP},
     utils::{get_all_project_names, get_all_projects, get_projects_with_config_path},
+    workspace::WorkspaceProtocol,
 };
 mod cli;
 
@@ -51,6 +80,21 @@ pub trait AddEsteemDevelopmentDependency {
     fn add_development_dependency(&mut self, dependency: String);
 }
 
+/// Used to add a peer dependency to a project or workspace
+pub trait AddEsteemPeerDependency {
+    fn add_peer_dependency(&mut self, dependency: String);
+}
+
+/// Used to add an optional dependency to a project or workspace
+pub trait AddEsteemOptionalDependency {
+    fn add_optional_dependency(&mut self, dependency: String);
+}
+
+/// Used to add a dependency that is only needed to run a target of a project or workspace
+pub trait AddEsteemTargetDependency {
+    fn add_target_dependency(&mut self, target: String, dependency: String);
+}
+
 /// Used to remove a required dependency to a project or workspace
 pub trait RemoveEsteemRequiredDependency {
     fn remove_required_dependency(
@@ -67,6 +111,27 @@ pub trait RemoveEsteemDevelopmentDependency {
     ) -> Result<(), LibraryError>;
 }
 
+/// Used to remove a peer dependency to a project or workspace
+pub trait RemoveEsteemPeerDependency {
+    fn remove_peer_dependency(&mut self, dependency: String) -> Result<(), LibraryError>;
+}
+
+/// Used to remove an optional dependency to a project or workspace
+pub trait RemoveEsteemOptionalDependency {
+    fn remove_optional_dependency(
+        &mut self,
+        dependency: String,
+    ) -> Result<(), LibraryError>;
+}
+
+/// Used to remove a dependency from all targets of a project or workspace
+pub trait RemoveEsteemTargetDependency {
+    fn remove_target_dependency(
+        &mut self,
+        dependency: String,
+    ) -> Result<(), LibraryError>;
+}
+
 /// Used to write dependencies to a file
 pub trait WriteDependencies
 where
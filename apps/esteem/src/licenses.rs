@@ -0,0 +1,356 @@
+use super::{
+    boundaries::matches_pattern, config::LicensePolicy, node_modules::Installation,
+    project::EsteemProject,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+/// whether `policy` applies to `project`
+fn applies_to(policy: &LicensePolicy, project: &EsteemProject) -> bool {
+    let tags = project.get_tags();
+    policy
+        .source_tag
+        .as_ref()
+        .is_none_or(|tag| tag == "*" || tags.iter().any(|t| matches_pattern(tag, t)))
+        && policy
+            .project_type
+            .as_deref()
+            .is_none_or(|project_type| project.get_project_type() == Some(project_type))
+}
+
+/// describes the projects that `policy` applies to, eg: `tag "type:app"`
+fn describe(policy: &LicensePolicy) -> String {
+    let selectors = policy
+        .source_tag
+        .iter()
+        .map(|tag| format!("tag {tag:?}"))
+        .chain(
+            policy
+                .project_type
+                .iter()
+                .map(|project_type| format!("type {project_type:?}")),
+        )
+        .collect::<Vec<_>>();
+    match selectors.is_empty() {
+        true => "all projects".to_owned(),
+        false => selectors.join(", "),
+    }
+}
+
+/// An SPDX license expression, eg: `(MIT OR Apache-2.0) AND BSD-3-Clause`
+#[derive(Debug, PartialEq, Eq)]
+enum LicenseExpression {
+    /// a license, eg: `GPL-2.0+`, optionally with an exception, eg:
+    /// `GPL-2.0-only WITH Classpath-exception-2.0`
+    License {
+        id: String,
+        exception: Option<String>,
+    },
+    And(Box<Self>, Box<Self>),
+    Or(Box<Self>, Box<Self>),
+}
+
+impl LicenseExpression {
+    /// parses an expression where `WITH` binds tighter than `AND`, which binds tighter
+    /// than `OR`. A license that is not a valid expression (eg: `SEE LICENSE IN
+    /// LICENSE.md`) is a single license.
+    fn parse(expression: &str) -> Self {
+        let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+        let tokens = spaced.split_whitespace().collect::<Vec<_>>();
+        let mut remaining = tokens.as_slice();
+        match Self::parse_or(&mut remaining) {
+            Some(parsed) if remaining.is_empty() => parsed,
+            _ => Self::License {
+                id: expression.trim().to_owned(),
+                exception: None,
+            },
+        }
+    }
+
+    fn parse_or(tokens: &mut &[&str]) -> Option<Self> {
+        let mut parsed = Self::parse_and(tokens)?;
+        while let Some((&"OR", rest)) = tokens.split_first() {
+            *tokens = rest;
+            parsed = Self::Or(Box::new(parsed), Box::new(Self::parse_and(tokens)?));
+        }
+        Some(parsed)
+    }
+
+    fn parse_and(tokens: &mut &[&str]) -> Option<Self> {
+        let mut parsed = Self::parse_license(tokens)?;
+        while let Some((&"AND", rest)) = tokens.split_first() {
+            *tokens = rest;
+            parsed = Self::And(Box::new(parsed), Box::new(Self::parse_license(tokens)?));
+        }
+        Some(parsed)
+    }
+
+    fn parse_license(tokens: &mut &[&str]) -> Option<Self> {
+        let (&token, rest) = tokens.split_first()?;
+        *tokens = rest;
+        match token {
+            "(" => {
+                let parsed = Self::parse_or(tokens)?;
+                let (&")", rest) = tokens.split_first()? else {
+                    return None;
+                };
+                *tokens = rest;
+                Some(parsed)
+            }
+            ")" | "AND" | "OR" | "WITH" => None,
+            id => {
+                let exception = match tokens {
+                    ["WITH", exception, rest @ ..] if is_operand(exception) => {
+                        let exception = exception.to_string();
+                        *tokens = rest;
+                        Some(exception)
+                    }
+                    ["WITH", ..] => return None,
+                    _ => None,
+                };
+                Some(Self::License {
+                    id: id.to_owned(),
+                    exception,
+                })
+            }
+        }
+    }
+
+    /// whether `policy` accepts the expression. A license with an exception is accepted
+    /// if either the license alone or the license with its exception (eg:
+    /// `GPL-2.0-only WITH Classpath-exception-2.0`) is accepted.
+    fn is_accepted(&self, policy: &LicensePolicy) -> bool {
+        match self {
+            Self::License { id, exception } => {
+                is_accepted(policy, id)
+                    || exception
+                        .as_ref()
+                        .is_some_and(|e| is_accepted(policy, &format!("{id} WITH {e}")))
+            }
+            Self::And(left, right) => {
+                left.is_accepted(policy) && right.is_accepted(policy)
+            }
+            Self::Or(left, right) => {
+                left.is_accepted(policy) || right.is_accepted(policy)
+            }
+        }
+    }
+}
+
+fn is_operand(token: &str) -> bool {
+    !["(", ")", "AND", "OR", "WITH"].contains(&token)
+}
+
+/// whether `policy` accepts a single license
+fn is_accepted(policy: &LicensePolicy, license: &str) -> bool {
+    policy
+        .allow
+        .as_ref()
+        .is_none_or(|allow| allow.iter().any(|p| matches_pattern(p, license)))
+        && !policy.deny.iter().any(|p| matches_pattern(p, license))
+}
+
+/// checks the licenses of the packages installed for `project` (`closure`) against the
+/// policies that apply to it and returns a description of each violation. `direct` maps
+/// the packages that the project depends on directly to the packages installed for them,
+/// to report which ones pull a violating package in.
+pub(crate) fn check_licenses(
+    policies: &[LicensePolicy],
+    project: &EsteemProject,
+    installation: &Installation,
+    closure: &BTreeSet<PathBuf>,
+    direct: &BTreeMap<String, BTreeSet<PathBuf>>,
+) -> Vec<String> {
+    let mut violations = vec![];
+    let applicable = policies
+        .iter()
+        .filter(|policy| applies_to(policy, project))
+        .collect::<Vec<_>>();
+    for directory in closure {
+        let package = installation.get(directory);
+        let name = format!(
+            "{}@{}",
+            package.name,
+            package.version.as_deref().unwrap_or("unknown")
+        );
+        let via = direct
+            .iter()
+            .filter(|(direct, closure)| {
+                **direct != package.name && closure.contains(directory)
+            })
+            .map(|(direct, _)| direct.as_str())
+            .collect::<Vec<_>>();
+        let via = match via.is_empty() {
+            true => String::new(),
+            false => format!(", required by {via:?}"),
+        };
+        for policy in &applicable {
+            if policy
+                .ignore_packages
+                .iter()
+                .any(|pattern| matches_pattern(pattern, &package.name))
+            {
+                continue;
+            }
+            match &package.license {
+                Some(license)
+                    if !LicenseExpression::parse(license).is_accepted(policy) =>
+                {
+                    violations.push(format!(
+                        "{:?} ({}) may not use {name:?} licensed under {license:?}{via}",
+                        project.name,
+                        describe(policy)
+                    ))
+                }
+                Some(_) => {}
+                None if policy.allow.is_some() => violations.push(format!(
+                    "{:?} ({}) may only use packages licensed under {:?}, but {name:?} has no license{via}",
+                    project.name,
+                    describe(policy),
+                    policy.allow.as_ref().unwrap()
+                )),
+                None => {}
+            }
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow: Option<&[&str]>, deny: &[&str]) -> LicensePolicy {
+        let to_strings =
+            |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect();
+        LicensePolicy {
+            source_tag: None,
+            project_type: None,
+            allow: allow.map(to_strings),
+            deny: to_strings(deny),
+            ignore_packages: vec![],
+        }
+    }
+
+    fn license(id: &str, exception: Option<&str>) -> LicenseExpression {
+        LicenseExpression::License {
+            id: id.to_owned(),
+            exception: exception.map(String::from),
+        }
+    }
+
+    fn is_accepted_by(expression: &str, policy: &LicensePolicy) -> bool {
+        LicenseExpression::parse(expression).is_accepted(policy)
+    }
+
+    #[test]
+    fn parses_with_precedence() {
+        use LicenseExpression::{And, Or};
+        assert_eq!(
+            LicenseExpression::parse("MIT OR Apache-2.0 AND BSD-3-Clause"),
+            Or(
+                Box::new(license("MIT", None)),
+                Box::new(And(
+                    Box::new(license("Apache-2.0", None)),
+                    Box::new(license("BSD-3-Clause", None))
+                ))
+            )
+        );
+        assert_eq!(
+            LicenseExpression::parse("(MIT OR Apache-2.0) AND GPL-2.0+"),
+            And(
+                Box::new(Or(
+                    Box::new(license("MIT", None)),
+                    Box::new(license("Apache-2.0", None))
+                )),
+                Box::new(license("GPL-2.0+", None))
+            )
+        );
+        assert_eq!(
+            LicenseExpression::parse("GPL-2.0-only WITH Classpath-exception-2.0 OR MIT"),
+            Or(
+                Box::new(license("GPL-2.0-only", Some("Classpath-exception-2.0"))),
+                Box::new(license("MIT", None))
+            )
+        );
+    }
+
+    #[test]
+    fn parses_invalid_expressions_as_a_single_license() {
+        for expression in [
+            "SEE LICENSE IN LICENSE.md",
+            "(MIT OR Apache-2.0",
+            "MIT OR",
+            "MIT AND (ISC))",
+            "MIT WITH",
+            "()",
+        ] {
+            assert_eq!(
+                LicenseExpression::parse(expression),
+                license(expression, None),
+                "{expression:?}"
+            );
+        }
+        let deny = policy(None, &["SEE LICENSE*"]);
+        assert!(!is_accepted_by("SEE LICENSE IN LICENSE.md", &deny));
+    }
+
+    #[test]
+    fn rejects_conjunctions_with_a_denied_license() {
+        let deny = policy(None, &["GPL-*"]);
+        assert!(!is_accepted_by(
+            "(MIT OR Apache-2.0) AND GPL-3.0-only",
+            &deny
+        ));
+        assert!(!is_accepted_by(
+            "GPL-3.0-only AND (MIT OR Apache-2.0)",
+            &deny
+        ));
+        assert!(is_accepted_by(
+            "(MIT OR GPL-3.0-only) AND Apache-2.0",
+            &deny
+        ));
+    }
+
+    #[test]
+    fn accepts_any_accepted_alternative() {
+        let deny = policy(None, &["GPL-*"]);
+        assert!(is_accepted_by("MIT OR GPL-2.0", &deny));
+        assert!(is_accepted_by("(GPL-2.0 OR (ISC AND MIT))", &deny));
+        assert!(!is_accepted_by("GPL-2.0 OR (ISC AND GPL-3.0)", &deny));
+        let allow = policy(Some(&["MIT", "BSD-*"]), &[]);
+        assert!(is_accepted_by(
+            "((Apache-2.0 AND ISC) OR (MIT AND BSD-2-Clause))",
+            &allow
+        ));
+        assert!(!is_accepted_by(
+            "((Apache-2.0 AND MIT) OR (ISC AND BSD-2-Clause))",
+            &allow
+        ));
+    }
+
+    #[test]
+    fn accepts_exceptions_of_accepted_licenses() {
+        let allow = policy(
+            Some(&["MIT", "GPL-2.0-only WITH Classpath-exception-2.0"]),
+            &[],
+        );
+        assert!(is_accepted_by(
+            "GPL-2.0-only WITH Classpath-exception-2.0",
+            &allow
+        ));
+        assert!(is_accepted_by("MIT WITH LLVM-exception", &allow));
+        assert!(!is_accepted_by("GPL-2.0-only", &allow));
+        assert!(!is_accepted_by(
+            "GPL-2.0-only WITH GCC-exception-2.0",
+            &allow
+        ));
+        let deny = policy(None, &["GPL-*"]);
+        assert!(!is_accepted_by(
+            "GPL-2.0-only WITH Classpath-exception-2.0",
+            &deny
+        ));
+    }
+}
@@ -0,0 +1,452 @@
+use super::{constants::LOCKFILES, specifier::split_name_and_range, LibraryError};
+use serde_json::Value;
+use std::{collections::BTreeMap, fs::read_to_string, path::Path};
+
+/// The prefix of the ranges of packages installed under a different name
+const ALIAS_PREFIX: &str = "npm:";
+
+/// A package as the lockfile resolves it
+#[derive(Debug, Default, Clone)]
+pub(crate) struct LockedPackage {
+    /// the Subresource Integrity of its tarball, eg: `sha512-...`
+    pub(crate) integrity: Option<String>,
+    /// where its tarball is downloaded from
+    pub(crate) resolved: Option<String>,
+}
+
+/// The packages of the lockfile of the workspace, by name and version. The lockfiles of
+/// npm (`package-lock.json`), pnpm (`pnpm-lock.yaml`) and yarn (`yarn.lock`) are read.
+#[derive(Debug, Default)]
+pub(crate) struct Lockfile(BTreeMap<(String, String), LockedPackage>);
+
+impl Lockfile {
+    /// reads the first lockfile that exists at the root of the workspace, the lockfile is
+    /// empty if there is none
+    pub(crate) fn from_current_directory() -> Result<Self, LibraryError> {
+        let path = match LOCKFILES.iter().map(Path::new).find(|p| p.exists()) {
+            Some(path) => path,
+            None => {
+                warn!("The workspace does not have a lockfile");
+                return Ok(Self::default());
+            }
+        };
+        let data = read_to_string(path)
+            .map_err(|err| LibraryError(format!("Could not read {path:?}: {err}")))?;
+        let packages = match path.to_str() {
+            Some("package-lock.json") => parse_package_lock(&data),
+            Some("pnpm-lock.yaml") => parse_pnpm_lock(&data),
+            _ => Ok(parse_yarn_lock(&data)),
+        }
+        .map_err(|err| LibraryError(format!("Could not parse {path:?}: {err}")))?;
+        info!("Read {} packages from {path:?}", packages.len());
+        Ok(Self(packages))
+    }
+
+    pub(crate) fn get(&self, name: &str, version: &str) -> Option<&LockedPackage> {
+        self.0.get(&(name.to_owned(), version.to_owned()))
+    }
+}
+
+type Packages = BTreeMap<(String, String), LockedPackage>;
+
+fn get_string(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(String::from)
+}
+
+/// reads `packages` (lockfile versions 2 and 3) whose keys are the paths the packages are
+/// installed at, or the nested `dependencies` of version 1
+fn parse_package_lock(data: &str) -> Result<Packages, String> {
+    let lockfile = serde_json::from_str::<Value>(data).map_err(|err| err.to_string())?;
+    let mut ret = Packages::new();
+    if let Some(packages) = lockfile.get("packages").and_then(Value::as_object) {
+        for (path, package) in packages {
+            let name = get_string(package, "name").or_else(|| {
+                path.rsplit_once("node_modules/")
+                    .map(|(_, name)| name.to_owned())
+            });
+            if let (Some(name), Some(version)) = (name, get_string(package, "version")) {
+                ret.insert((name, version), read_locked_package(package));
+            }
+        }
+        return Ok(ret);
+    }
+    let mut to_visit = vec![lockfile.get("dependencies")];
+    while let Some(dependencies) = to_visit.pop() {
+        for (name, package) in dependencies
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(version) = get_string(package, "version") {
+                ret.insert((name.to_owned(), version), read_locked_package(package));
+            }
+            to_visit.push(package.get("dependencies"));
+        }
+    }
+    Ok(ret)
+}
+
+fn read_locked_package(package: &Value) -> LockedPackage {
+    LockedPackage {
+        integrity: get_string(package, "integrity"),
+        resolved: get_string(package, "resolved"),
+    }
+}
+
+/// reads `packages`, whose keys are `/name/version` (version 5), `/name@version`
+/// (version 6) or `name@version` (version 9), optionally followed by the versions of
+/// their peer dependencies
+fn parse_pnpm_lock(data: &str) -> Result<Packages, String> {
+    let lockfile = serde_yaml::from_str::<Value>(data).map_err(|err| err.to_string())?;
+    let mut ret = Packages::new();
+    let packages = match lockfile.get("packages").and_then(Value::as_object) {
+        Some(packages) => packages,
+        None => return Ok(ret),
+    };
+    let version = match lockfile.get("lockfileVersion") {
+        Some(Value::Number(version)) => version.as_f64(),
+        Some(Value::String(version)) => version.parse().ok(),
+        _ => None,
+    };
+    let has_slashed_keys = version.is_some_and(|v| v < 6.0);
+    for (key, package) in packages {
+        let key = key.strip_prefix('/').unwrap_or(key);
+        let parsed = match has_slashed_keys {
+            true => key.rsplit_once('/').map(|(name, version)| {
+                let version = version.split('_').next().unwrap_or(version);
+                (name.to_owned(), version.to_owned())
+            }),
+            false => match split_name_and_range(key.split('(').next().unwrap_or(key)) {
+                (name, Some(version)) => Some((name.to_owned(), version.to_owned())),
+                _ => None,
+            },
+        };
+        let name = get_string(package, "name").or(parsed.as_ref().map(|p| p.0.clone()));
+        let version =
+            get_string(package, "version").or(parsed.as_ref().map(|p| p.1.clone()));
+        if let (Some(name), Some(version)) = (name, version) {
+            let resolution = package.get("resolution").cloned().unwrap_or_default();
+            let locked = LockedPackage {
+                integrity: get_string(&resolution, "integrity"),
+                resolved: get_string(&resolution, "tarball"),
+            };
+            ret.insert((name, version), locked);
+        }
+    }
+    Ok(ret)
+}
+
+/// reads the entries of a `yarn.lock`, which start with the specifiers they resolve (eg:
+/// `react@^18.0.0, react@^18.2.0:`) followed by indented fields, the more indented
+/// lines are the entries of its fields (eg: `dependencies`). Yarn 2+ records its own
+/// checksums instead of integrities, so only the versions are read from them.
+fn parse_yarn_lock(data: &str) -> Packages {
+    let mut ret = Packages::new();
+    let mut entries = Vec::<(String, BTreeMap<String, String>)>::new();
+    for line in data.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        match line.starts_with(' ') {
+            true if line.starts_with("   ") => {}
+            true => {
+                if let Some((_, fields)) = entries.last_mut() {
+                    let line = line.trim();
+                    let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+                    let value = value.trim().trim_matches('"');
+                    fields.insert(key.trim_end_matches(':').to_owned(), value.to_owned());
+                }
+            }
+            false => {
+                let specifier =
+                    line.trim_end_matches(':').split(',').next().unwrap_or("");
+                let specifier = specifier.trim().trim_matches('"');
+                // the header of the lockfiles of yarn 2+
+                if specifier == "__metadata" {
+                    entries.push((String::new(), BTreeMap::new()));
+                    continue;
+                }
+                entries.push((get_yarn_package_name(specifier), BTreeMap::new()));
+            }
+        }
+    }
+    for (name, mut fields) in entries.into_iter().filter(|(name, _)| !name.is_empty()) {
+        if let Some(version) = fields.remove("version") {
+            let locked = LockedPackage {
+                integrity: fields.remove("integrity"),
+                resolved: fields.remove("resolved"),
+            };
+            ret.insert((name, version), locked);
+        }
+    }
+    ret
+}
+
+/// returns the name of the package that a specifier of `yarn.lock` resolves, which is
+/// the aliased package for `alias@npm:package@range`
+fn get_yarn_package_name(specifier: &str) -> String {
+    let (name, range) = split_name_and_range(specifier);
+    match range.and_then(|r| r.strip_prefix(ALIAS_PREFIX)) {
+        Some(aliased) => match split_name_and_range(aliased) {
+            (aliased, Some(_)) => aliased.to_owned(),
+            _ => name.to_owned(),
+        },
+        None => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get<'a>(packages: &'a Packages, name: &str, version: &str) -> &'a LockedPackage {
+        packages
+            .get(&(name.to_owned(), version.to_owned()))
+            .unwrap_or_else(|| panic!("{name}@{version} is not in {packages:?}"))
+    }
+
+    #[test]
+    fn parses_package_lock_v1() {
+        let data = r#"{
+            "lockfileVersion": 1,
+            "dependencies": {
+                "react": {
+                    "version": "18.2.0",
+                    "resolved": "https://registry.npmjs.org/react/-/react-18.2.0.tgz",
+                    "integrity": "sha512-react"
+                },
+                "@babel/core": {
+                    "version": "7.20.0",
+                    "integrity": "sha512-core",
+                    "dependencies": {
+                        "semver": { "version": "6.3.0", "integrity": "sha512-semver6" }
+                    }
+                },
+                "semver": { "version": "7.3.8", "integrity": "sha512-semver7" }
+            }
+        }"#;
+        let packages = parse_package_lock(data).unwrap();
+        assert_eq!(packages.len(), 4);
+        let react = get(&packages, "react", "18.2.0");
+        assert_eq!(react.integrity.as_deref(), Some("sha512-react"));
+        assert_eq!(
+            react.resolved.as_deref(),
+            Some("https://registry.npmjs.org/react/-/react-18.2.0.tgz")
+        );
+        assert_eq!(
+            get(&packages, "semver", "6.3.0").integrity.as_deref(),
+            Some("sha512-semver6")
+        );
+        assert_eq!(
+            get(&packages, "semver", "7.3.8").integrity.as_deref(),
+            Some("sha512-semver7")
+        );
+        assert!(get(&packages, "@babel/core", "7.20.0").resolved.is_none());
+    }
+
+    #[test]
+    fn parses_package_lock_v3() {
+        let data = r#"{
+            "lockfileVersion": 3,
+            "packages": {
+                "": { "name": "workspace", "workspaces": ["libs/*"] },
+                "libs/ui": { "name": "@acme/ui", "version": "0.1.0" },
+                "node_modules/@acme/ui": { "resolved": "libs/ui", "link": true },
+                "node_modules/@babel/core": {
+                    "version": "7.20.0",
+                    "integrity": "sha512-core"
+                },
+                "node_modules/@babel/core/node_modules/semver": {
+                    "version": "6.3.0",
+                    "integrity": "sha512-semver6"
+                },
+                "node_modules/aliased": {
+                    "name": "react",
+                    "version": "18.2.0",
+                    "integrity": "sha512-react"
+                }
+            }
+        }"#;
+        let packages = parse_package_lock(data).unwrap();
+        assert_eq!(packages.len(), 4);
+        assert_eq!(
+            get(&packages, "@babel/core", "7.20.0").integrity.as_deref(),
+            Some("sha512-core")
+        );
+        assert_eq!(
+            get(&packages, "semver", "6.3.0").integrity.as_deref(),
+            Some("sha512-semver6")
+        );
+        assert_eq!(
+            get(&packages, "react", "18.2.0").integrity.as_deref(),
+            Some("sha512-react")
+        );
+        get(&packages, "@acme/ui", "0.1.0");
+    }
+
+    #[test]
+    fn parses_pnpm_lock_v5() {
+        let data = r#"
+lockfileVersion: 5.4
+packages:
+  /react/18.2.0:
+    resolution: {integrity: sha512-react}
+  /react-dom/18.2.0_react@18.2.0:
+    resolution: {integrity: sha512-dom}
+  /@babel/core/7.20.0:
+    resolution: {integrity: sha512-core, tarball: https://example.com/core.tgz}
+"#;
+        let packages = parse_pnpm_lock(data).unwrap();
+        assert_eq!(packages.len(), 3);
+        assert_eq!(
+            get(&packages, "react-dom", "18.2.0").integrity.as_deref(),
+            Some("sha512-dom")
+        );
+        let core = get(&packages, "@babel/core", "7.20.0");
+        assert_eq!(core.integrity.as_deref(), Some("sha512-core"));
+        assert_eq!(
+            core.resolved.as_deref(),
+            Some("https://example.com/core.tgz")
+        );
+        get(&packages, "react", "18.2.0");
+    }
+
+    #[test]
+    fn parses_pnpm_lock_v6() {
+        let data = r#"
+lockfileVersion: '6.0'
+packages:
+  /react@18.2.0:
+    resolution: {integrity: sha512-react}
+  /react-dom@18.2.0(react@18.2.0):
+    resolution: {integrity: sha512-dom}
+  /@babel/core@7.20.0:
+    resolution: {integrity: sha512-core}
+  /aliased@1.0.0:
+    resolution: {integrity: sha512-lib}
+    name: lib
+    version: 1.0.1
+"#;
+        let packages = parse_pnpm_lock(data).unwrap();
+        assert_eq!(packages.len(), 4);
+        assert_eq!(
+            get(&packages, "react-dom", "18.2.0").integrity.as_deref(),
+            Some("sha512-dom")
+        );
+        assert_eq!(
+            get(&packages, "lib", "1.0.1").integrity.as_deref(),
+            Some("sha512-lib")
+        );
+        get(&packages, "react", "18.2.0");
+        get(&packages, "@babel/core", "7.20.0");
+    }
+
+    #[test]
+    fn parses_pnpm_lock_v9() {
+        let data = r#"
+lockfileVersion: '9.0'
+packages:
+  react@18.2.0:
+    resolution: {integrity: sha512-react}
+  '@babel/core@7.20.0':
+    resolution: {integrity: sha512-core}
+snapshots:
+  react-dom@18.2.0(react@18.2.0):
+    dependencies:
+      react: 18.2.0
+"#;
+        let packages = parse_pnpm_lock(data).unwrap();
+        assert_eq!(packages.len(), 2);
+        assert_eq!(
+            get(&packages, "react", "18.2.0").integrity.as_deref(),
+            Some("sha512-react")
+        );
+        assert_eq!(
+            get(&packages, "@babel/core", "7.20.0").integrity.as_deref(),
+            Some("sha512-core")
+        );
+    }
+
+    #[test]
+    fn parses_yarn_lock_v1() {
+        let data = r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@babel/core@^7.0.0", "@babel/core@^7.20.0":
+  version "7.20.0"
+  resolved "https://registry.yarnpkg.com/@babel/core/-/core-7.20.0.tgz#abc"
+  integrity sha512-core
+  dependencies:
+    version "^1.0.0"
+
+react@^18.0.0, react@^18.2.0:
+  version "18.2.0"
+  resolved "https://registry.yarnpkg.com/react/-/react-18.2.0.tgz#def"
+  integrity sha512-react
+
+my-react@npm:react@17:
+  version "17.0.2"
+  integrity sha512-react17
+"#;
+        let packages = parse_yarn_lock(data);
+        assert_eq!(packages.len(), 3);
+        let core = get(&packages, "@babel/core", "7.20.0");
+        assert_eq!(core.integrity.as_deref(), Some("sha512-core"));
+        assert_eq!(
+            core.resolved.as_deref(),
+            Some("https://registry.yarnpkg.com/@babel/core/-/core-7.20.0.tgz#abc")
+        );
+        assert_eq!(
+            get(&packages, "react", "18.2.0").integrity.as_deref(),
+            Some("sha512-react")
+        );
+        assert_eq!(
+            get(&packages, "react", "17.0.2").integrity.as_deref(),
+            Some("sha512-react17")
+        );
+    }
+
+    #[test]
+    fn parses_yarn_lock_berry() {
+        let data = r#"# This file is generated by running "yarn install" inside your project.
+
+__metadata:
+  version: 6
+  cacheKey: 8
+
+"@babel/core@npm:^7.0.0, @babel/core@npm:^7.20.0":
+  version: 7.20.0
+  resolution: "@babel/core@npm:7.20.0"
+  dependencies:
+    semver: ^6.3.0
+  checksum: abcdef
+  languageName: node
+  linkType: hard
+
+"react@npm:^18.2.0":
+  version: 18.2.0
+  resolution: "react@npm:18.2.0"
+  checksum: 123456
+  languageName: node
+  linkType: hard
+"#;
+        let packages = parse_yarn_lock(data);
+        assert_eq!(packages.len(), 2);
+        let core = get(&packages, "@babel/core", "7.20.0");
+        assert!(core.integrity.is_none());
+        get(&packages, "react", "18.2.0");
+    }
+
+    #[test]
+    fn reads_the_names_of_yarn_specifiers() {
+        assert_eq!(get_yarn_package_name("react@^18.0.0"), "react");
+        assert_eq!(get_yarn_package_name("@babel/core@^7.0.0"), "@babel/core");
+        assert_eq!(get_yarn_package_name("react@npm:^18.2.0"), "react");
+        assert_eq!(get_yarn_package_name("my-react@npm:react@17"), "react");
+        assert_eq!(
+            get_yarn_package_name("types@npm:@types/node@18"),
+            "@types/node"
+        );
+    }
+}
@@ -1,18 +1,36 @@
-use clap::{app_from_crate, arg, App, AppSettings};
+use clap::{app_from_crate, arg, App, AppSettings, Arg, ArgMatches};
 use env_logger::Env;
 use esteem::{
-    get_all_project_names, perform_add, perform_init, perform_install_isolated,
-    perform_remove, perform_workspace_add, perform_workspace_remove,
-    utils_get_dependencies,
+    get_all_project_names, perform_add, perform_boundaries, perform_check,
+    perform_cycles, perform_docker, perform_generate_package_json, perform_graph,
+    perform_init, perform_install, perform_install_isolated, perform_licenses,
+    perform_list, perform_optimize, perform_remove, perform_sbom, perform_size,
+    perform_workspace_add, perform_workspace_remove, utils_get_dependencies, AddOptions,
+    Direction, EsteemConfig, GraphFormat, InstallIsolatedOptions, RequirementScope,
+    SbomFormat, SizeFormat, WorkspaceProtocol, DEFAULT_BASE_IMAGE, DEFAULT_MAX_USERS,
+    DEFAULT_MIN_SHARE, DEFAULT_TOP,
 };
+use std::{fs::write, path::PathBuf};
 
 #[macro_use]
 extern crate log;
 
 const ADD_COMMAND: &str = "add";
+const BOUNDARIES_COMMAND: &str = "boundaries";
+const CHECK_COMMAND: &str = "check";
+const CYCLES_COMMAND: &str = "cycles";
+const DOCKER_COMMAND: &str = "docker";
+const GENERATE_PACKAGE_JSON_COMMAND: &str = "generate-package-json";
 const INIT_COMMAND: &str = "init";
+const INSTALL_COMMAND: &str = "install";
 const INSTALL_ISOLATED_COMMAND: &str = "install-isolated";
+const LICENSES_COMMAND: &str = "licenses";
+const LIST_COMMAND: &str = "list";
+const OPTIMIZE_COMMAND: &str = "optimize";
+const SIZE_COMMAND: &str = "size";
+const SBOM_COMMAND: &str = "sbom";
 const GET_DEPENDENCIES_COMMAND: &str = "get-dependencies";
+const GRAPH_COMMAND: &str = "graph";
 const REMOVE_COMMAND: &str = "remove";
 const UTILS_SUBCOMMAND: &str = "utils";
 const WORKSPACE_SUBCOMMAND: &str = "workspace";
@@ -21,8 +39,49 @@ const CALL_SCRIPT_EXECUTOR: &str = "call";
 const PROJECT_NAME: &str = "PROJECT_NAME";
 const DEPENDENCIES: &str = "DEPENDENCIES";
 const DEVELOPMENT: &str = "development";
+const PEER: &str = "peer";
+const OPTIONAL: &str = "optional";
+const EXACT: &str = "exact";
+const FROZEN: &str = "frozen";
+const PRODUCTION: &str = "production";
+const INSTALL: &str = "install";
+const RESTORE_AFTER: &str = "restore-after";
+const PRUNE_OVERRIDES: &str = "prune-overrides";
+const WITHOUT_GRAPH: &str = "without-graph";
+const WORKSPACE_PROTOCOL: &str = "workspace-protocol";
+const SKIP_IMPLICIT: &str = "skip-implicit";
+const TARGET: &str = "target";
+const TYPES: &str = "types";
+const MAX_USERS: &str = "max-users";
+const MIN_SHARE: &str = "min-share";
+const APPLY: &str = "apply";
+const TOP: &str = "top";
+const BASE_IMAGE: &str = "base-image";
+const OUTPUT: &str = "output";
+const CARET: &str = "caret";
+const ONLY: &str = "only";
+const INCLUDE: &str = "include";
+const ARGS: &str = "ARGS";
 const SKIP: &str = "skip";
 const PROJECTS: &str = "PROJECTS";
+const DIRECTION: &str = "direction";
+const FORMAT: &str = "format";
+const WITH_DEPENDENCIES: &str = "with-dependencies";
+const ALLOW: &str = "allow";
+const NO_CACHE: &str = "no-cache";
+const CACHE: &str = "cache";
+const NO_CALL: &str = "no-call";
+const NO_SKIP: &str = "no-skip";
+const NO_EXACT: &str = "no-exact";
+const NO_TYPES: &str = "no-types";
+const NO_FROZEN: &str = "no-frozen";
+const NO_PRODUCTION: &str = "no-production";
+const NO_INSTALL: &str = "no-install";
+const NO_RESTORE_AFTER: &str = "no-restore-after";
+const NO_PRUNE_OVERRIDES: &str = "no-prune-overrides";
+const NO_SKIP_IMPLICIT: &str = "no-skip-implicit";
+const NO_WITH_DEPENDENCIES: &str = "no-with-dependencies";
+const NO_CARET: &str = "no-caret";
 
 fn main() -> Result<(), String> {
     env_logger::Builder::from_env(Env::default().default_filter_or("info"))
@@ -30,15 +89,46 @@ fn main() -> Result<(), String> {
         .format_target(true)
         .init();
 
+    let config = EsteemConfig::load()?;
+
     let project_names = get_all_project_names();
     let project_names = &project_names
         .iter()
         .map(String::as_str)
         .collect::<Vec<&str>>();
 
+    let args_arg = arg!([ARGS])
+        .multiple_values(true)
+        .last(true)
+        .help("Extra arguments passed to the package manager, after `--`");
+
     let add_subcommand = App::new(ADD_COMMAND)
         .arg(arg!(-s - -skip).help("Skip calling the NPM package manager"))
-        .arg(arg!(-D - -development).help("Add as development dependencies"));
+        .arg(negation(SKIP, NO_SKIP, "Call the package manager, even if `skip` is set in the configuration"))
+        .arg(arg!(-D - -development).help("Add as development dependencies"))
+        .arg(
+            arg!(-P - -peer)
+                .conflicts_with(DEVELOPMENT)
+                .help("Add as peer dependencies"),
+        )
+        .arg(
+            arg!(-O - -optional)
+                .conflicts_with_all(&[DEVELOPMENT, PEER])
+                .help("Add as optional dependencies"),
+        )
+        .arg(arg!(-E - -exact).help("Install the exact version instead of a range"))
+        .arg(negation(EXACT, NO_EXACT, "Install a range, even if `exact` is set in the configuration"))
+        .arg(
+            arg!(--types)
+                .help("Also add the @types packages of dependencies without bundled types as development dependencies"),
+        )
+        .arg(negation(TYPES, NO_TYPES, "Do not add @types packages, even if `types` is set in the configuration"))
+        .arg(
+            arg!(-t --target <TARGET>)
+                .required(false)
+                .conflicts_with_all(&[PEER, OPTIONAL])
+                .help("Add as development dependencies only needed to run this target"),
+        );
 
     let deps_arg = arg!(<DEPENDENCIES>)
         .required(true)
@@ -47,6 +137,31 @@ fn main() -> Result<(), String> {
 
     let remove_subcommand = App::new(REMOVE_COMMAND).alias("rm");
 
+    let frozen_arg = arg!(--frozen).help("Do not update the lockfile while installing");
+
+    let production_arg = arg!(--production)
+        .help("Only install required dependencies, not development ones");
+
+    let no_frozen_arg = negation(
+        FROZEN,
+        NO_FROZEN,
+        "Allow updating the lockfile, even if `frozen` is set in the configuration",
+    );
+
+    let no_production_arg = negation(
+        PRODUCTION,
+        NO_PRODUCTION,
+        "Also include development dependencies, even if `production` is set in the configuration",
+    );
+
+    let install_subcommand = App::new(INSTALL_COMMAND)
+        .about("Install all packages of the workspace using the package manager")
+        .arg(frozen_arg.clone())
+        .arg(no_frozen_arg.clone())
+        .arg(production_arg.clone())
+        .arg(no_production_arg.clone())
+        .arg(args_arg.clone());
+
     let project_name_arg = arg!([PROJECT_NAME])
         .required(true)
         .help("The name of the project to make the changes in")
@@ -58,6 +173,21 @@ fn main() -> Result<(), String> {
     let skip_call_arg =
         arg!(-C - -call).help("Prefix the NX command with `npx`, `pnpm`, `yarn` etc");
 
+    let no_call_arg = negation(
+        CALL_SCRIPT_EXECUTOR,
+        NO_CALL,
+        "Do not prefix the NX command, even if `callScriptExecutor` is set in the configuration",
+    );
+
+    let no_cache_arg = arg!(--"no-cache")
+        .help("Calculate the project graph again instead of using the cached one");
+
+    let cache_arg = negation(
+        NO_CACHE,
+        CACHE,
+        "Use the cached project graph, even if `noCache` is set in the configuration",
+    );
+
     let install_isolated_subcommand = App::new(INSTALL_ISOLATED_COMMAND)
         .about("Isolate only dependencies of a few projects")
         .after_help(
@@ -70,20 +200,276 @@ fn main() -> Result<(), String> {
                 .help("The names of the projects whose dependencies should be installed")
                 .possible_values(project_names),
         )
-        .arg(skip_call_arg.clone());
+        .arg(
+            arg!(--only <SCOPES>)
+                .required(false)
+                .multiple_occurrences(true)
+                .use_delimiter(true)
+                .possible_values(RequirementScope::VARIANTS)
+                .help("Only write the dependencies of these requirement scopes [default: all]"),
+        )
+        .arg(
+            arg!(--include <SCOPES>)
+                .required(false)
+                .multiple_occurrences(true)
+                .use_delimiter(true)
+                .possible_values(RequirementScope::VARIANTS)
+                .help("Also write the dependencies of these requirement scopes, on top of `--only` or the scopes of the configuration"),
+        )
+        .arg(
+            arg!(-i - -install)
+                .help("Call the package manager to install the isolated dependencies"),
+        )
+        .arg(negation(INSTALL, NO_INSTALL, "Do not call the package manager, even if `install` is set in the configuration"))
+        .arg(frozen_arg)
+        .arg(no_frozen_arg)
+        .arg(production_arg)
+        .arg(no_production_arg)
+        .arg(arg!(--"restore-after").help(
+            "Restore the original `package.json` once the dependencies are installed",
+        ))
+        .arg(negation(RESTORE_AFTER, NO_RESTORE_AFTER, "Keep the isolated `package.json`, even if `restoreAfter` is set in the configuration"))
+        .arg(
+            arg!(--"prune-overrides")
+                .help("Remove the overrides and resolutions of packages that are not written"),
+        )
+        .arg(negation(PRUNE_OVERRIDES, NO_PRUNE_OVERRIDES, "Keep all overrides, even if `pruneOverrides` is set in the configuration"))
+        .arg(
+            arg!(--"without-graph")
+                .help("Use the given projects as they are instead of calculating their upstream projects"),
+        )
+        .arg(
+            arg!(--"workspace-protocol" <MODE>)
+                .required(false)
+                .help("What to write for the internal packages of the workspace [default: keep]")
+                .possible_values(WorkspaceProtocol::VARIANTS),
+        )
+        .arg(
+            arg!(-t --target <TARGET>)
+                .required(false)
+                .multiple_occurrences(true)
+                .use_delimiter(true)
+                .help("Only write the target dependencies needed to run these targets [default: all targets]"),
+        )
+        .arg(
+            arg!(--"skip-implicit")
+                .help("Do not write the development dependencies inferred from the targets of the projects"),
+        )
+        .arg(negation(SKIP_IMPLICIT, NO_SKIP_IMPLICIT, "Write the inferred development dependencies, even if `skipImplicit` is set in the configuration"))
+        .arg(skip_call_arg.clone())
+        .arg(no_call_arg.clone())
+        .arg(no_cache_arg.clone())
+        .arg(cache_arg.clone())
+        .arg(args_arg.clone());
+
+    let direction_arg = arg!(-d --direction <DIRECTION>)
+        .required(false)
+        .possible_values(Direction::VARIANTS)
+        .help("Which edges of the project graph to follow [default: both]");
+
+    let graph_subcommand = App::new(GRAPH_COMMAND)
+        .about("Export the project graph as Graphviz DOT, Mermaid or JSON")
+        .arg(
+            arg!([PROJECT_NAME])
+                .help("Only export the projects connected to this project")
+                .possible_values(project_names),
+        )
+        .arg(
+            arg!(-f --format <FORMAT>)
+                .required(false)
+                .help("The format to export the graph in [default: dot]")
+                .possible_values(GraphFormat::VARIANTS),
+        )
+        .arg(
+            arg!(-n - -"with-dependencies")
+                .help("Annotate projects with the number of packages they declare"),
+        )
+        .arg(negation(WITH_DEPENDENCIES, NO_WITH_DEPENDENCIES, "Do not annotate projects, even if `withDependencies` is set in the configuration"))
+        .arg(direction_arg.clone())
+        .arg(skip_call_arg.clone())
+        .arg(no_call_arg.clone())
+        .arg(no_cache_arg.clone())
+        .arg(cache_arg.clone());
+
+    let cycles_subcommand = App::new(CYCLES_COMMAND)
+        .about("Check the project graph for circular dependencies between projects")
+        .after_help(
+            "Exits with a non-zero status code if a cycle that is not allowed is found.",
+        )
+        .arg(
+            arg!(-a --allow <PROJECTS>)
+                .required(false)
+                .multiple_occurrences(true)
+                .help(
+                    "A comma separated list of the projects of a known cycle to ignore",
+                ),
+        )
+        .arg(skip_call_arg.clone())
+        .arg(no_call_arg.clone())
+        .arg(no_cache_arg.clone())
+        .arg(cache_arg.clone());
+
+    let boundaries_subcommand = App::new(BOUNDARIES_COMMAND)
+        .about("Check that projects respect the dependency constraints of their tags")
+        .after_help("The constraints are read from `boundaries.depConstraints` in the esteem configuration. Exits with a non-zero status code if a constraint is violated.")
+        .arg(skip_call_arg.clone())
+        .arg(no_call_arg.clone())
+        .arg(no_cache_arg.clone())
+        .arg(cache_arg.clone());
+
+    let list_subcommand = App::new(LIST_COMMAND)
+        .about("List the dependencies of a project, including the ones of its groups")
+        .arg(
+            arg!([PROJECT_NAME])
+                .help("The name of the project to list the dependencies of [default: the workspace]")
+                .possible_values(project_names),
+        );
+
+    let optimize_subcommand = App::new(OPTIMIZE_COMMAND)
+        .about("Suggest moving dependencies between the workspace and the projects")
+        .after_help("A workspace dependency used by only a few projects (in their sources, targets or declared dependencies) is moved into them, and a dependency declared by nearly every project is moved into the workspace.")
+        .arg(
+            arg!(--"max-users" <COUNT>)
+                .required(false)
+                .validator(|count| count.parse::<usize>())
+                .help("The most projects that may use a workspace dependency for it to be moved into them [default: 3]"),
+        )
+        .arg(
+            arg!(--"min-share" <SHARE>)
+                .required(false)
+                .validator(|share| match share.parse::<f64>() {
+                    Ok(share) if (0.0..=1.0).contains(&share) => Ok(()),
+                    _ => Err("must be a number between 0 and 1"),
+                })
+                .help("The least share of projects that must declare a dependency for it to be moved into the workspace [default: 0.9]"),
+        )
+        .arg(arg!(--apply).help("Write the suggested changes to the workspace and the projects"));
+
+    let size_subcommand = App::new(SIZE_COMMAND)
+        .about("Report the disk space used by the packages of projects installed in isolation")
+        .after_help("The sizes are read from node_modules, so the workspace must be installed. Each project is compared to the packages installed for the whole workspace.")
+        .arg(
+            arg!([PROJECTS])
+                .min_values(1)
+                .help("The names of the projects to report [default: all projects]")
+                .possible_values(project_names),
+        )
+        .arg(
+            arg!(-f --format <FORMAT>)
+                .required(false)
+                .help("The format to write the report in [default: table]")
+                .possible_values(SizeFormat::VARIANTS),
+        )
+        .arg(
+            arg!(--top <COUNT>)
+                .required(false)
+                .validator(|count| count.parse::<usize>())
+                .help("The number of the heaviest packages to list [default: 10]"),
+        )
+        .arg(skip_call_arg.clone())
+        .arg(no_call_arg.clone())
+        .arg(no_cache_arg.clone())
+        .arg(cache_arg.clone());
+
+    let check_subcommand = App::new(CHECK_COMMAND)
+        .about("Check that the packages projects declare or need exist in package.json")
+        .after_help("The packages of the executors of targets and the binaries called by `nx:run-commands` targets are needed by projects. Exits with a non-zero status code if a package is missing.")
+        .arg(
+            arg!([PROJECTS])
+                .min_values(1)
+                .help("The names of the projects to check [default: all projects]")
+                .possible_values(project_names),
+        );
+
+    let generate_package_json_subcommand = App::new(GENERATE_PACKAGE_JSON_COMMAND)
+        .about("Generate the package.json of a buildable or publishable project")
+        .after_help("The name and version of the projects are read from `publish` in the esteem configuration.")
+        .arg(project_name_arg.clone().help("The name of the project to generate the package.json for"))
+        .arg(
+            arg!(-o --output <DIRECTORY>)
+                .required(false)
+                .help("The directory to write to instead of the `outputPath` of the `build` target"),
+        )
+        .arg(arg!(--caret).help("Turn exact versions into caret ranges, eg: `1.2.3` into `^1.2.3`"))
+        .arg(negation(CARET, NO_CARET, "Keep exact versions, even if `caret` is set in the configuration"))
+        .arg(skip_call_arg.clone())
+        .arg(no_call_arg.clone())
+        .arg(no_cache_arg.clone())
+        .arg(cache_arg.clone());
+
+    let docker_subcommand = App::new(DOCKER_COMMAND)
+        .about("Generate a multi-stage Dockerfile and .dockerignore for a project")
+        .after_help("Build the image from the workspace root, eg: `docker build -f apps/server/Dockerfile .`")
+        .arg(project_name_arg.clone().help("The name of the project to generate the Dockerfile for"))
+        .arg(
+            arg!(-o --output <DIRECTORY>)
+                .required(false)
+                .help("The directory to write to instead of the root of the project"),
+        )
+        .arg(
+            arg!(--"base-image" <IMAGE>)
+                .required(false)
+                .help("The image that every stage is based on [default: node:18]"),
+        )
+        .arg(skip_call_arg.clone())
+        .arg(no_call_arg.clone())
+        .arg(no_cache_arg.clone())
+        .arg(cache_arg.clone());
+
+    let sbom_subcommand = App::new(SBOM_COMMAND)
+        .about("Generate the software bill of materials of a project as CycloneDX or SPDX")
+        .after_help("Every package installed for the project in isolation is listed with its version and license (read from node_modules) and its integrity (read from the lockfile).")
+        .arg(project_name_arg.clone().help("The name of the project to generate the bill of materials for"))
+        .arg(
+            arg!(-f --format <FORMAT>)
+                .required(false)
+                .help("The format of the bill of materials [default: cyclonedx]")
+                .possible_values(SbomFormat::VARIANTS),
+        )
+        .arg(
+            arg!(-o --output <FILE>)
+                .required(false)
+                .help("The file to write to instead of the standard output"),
+        )
+        .arg(arg!(--production).help("Only list the packages needed in production, not development ones"))
+        .arg(negation(PRODUCTION, NO_PRODUCTION, "Also list development packages, even if `production` is set in the configuration"))
+        .arg(skip_call_arg.clone())
+        .arg(no_call_arg.clone())
+        .arg(no_cache_arg.clone())
+        .arg(cache_arg.clone());
+
+    let licenses_subcommand = App::new(LICENSES_COMMAND)
+        .about("Check the licenses of the packages of projects against the policies of the configuration")
+        .after_help("Every package installed for a project in isolation is checked against the policies that apply to its tags or its type. The licenses are read from node_modules, so the workspace must be installed. Exits with an error when a license is not allowed.")
+        .arg(
+            arg!([PROJECTS])
+                .min_values(1)
+                .help("The names of the projects to check [default: all projects]")
+                .possible_values(project_names),
+        )
+        .arg(arg!(--production).help("Only check the packages needed in production, not development ones"))
+        .arg(negation(PRODUCTION, NO_PRODUCTION, "Also check development packages, even if `production` is set in the configuration"))
+        .arg(skip_call_arg.clone())
+        .arg(no_call_arg.clone())
+        .arg(no_cache_arg.clone())
+        .arg(cache_arg.clone());
 
     let utils_subcommand = App::new(UTILS_SUBCOMMAND)
         .about("Helpful utilities to manage projects more efficiently")
         .subcommand(
             App::new(GET_DEPENDENCIES_COMMAND)
-            .about("Get all projects that are connected to this project in the NX graphql")
-            .arg(
-                arg!([PROJECT_NAME])
-                    .required(true)
-                    .help("The name of the project whose dependencies you want to get")
-                    .possible_values(project_names),
-            )
-            .arg(skip_call_arg.clone()),
+                .about("Get all projects that are connected to this project in the NX graphql")
+                .arg(
+                    arg!([PROJECT_NAME])
+                        .required(true)
+                        .help("The name of the project whose dependencies you want to get")
+                        .possible_values(project_names),
+                )
+                .arg(direction_arg.clone())
+                .arg(skip_call_arg.clone())
+                .arg(no_call_arg.clone())
+                .arg(no_cache_arg.clone())
+                .arg(cache_arg.clone()),
         );
 
     let workspace_subcommand = App::new(WORKSPACE_SUBCOMMAND)
@@ -92,12 +478,14 @@ fn main() -> Result<(), String> {
             add_subcommand
                 .clone()
                 .arg(deps_arg.clone())
+                .arg(args_arg.clone())
                 .about("Install dependencies to a workspace"),
         )
         .subcommand(
             remove_subcommand
                 .clone()
                 .arg(deps_arg.clone())
+                .arg(args_arg.clone())
                 .about("Removes dependencies from a workspace (alias: rm)"),
         );
 
@@ -111,6 +499,7 @@ fn main() -> Result<(), String> {
                 .clone()
                 .arg(&project_name_arg)
                 .arg(&deps_arg)
+                .arg(&args_arg)
                 .about("Installs dependencies to a project"),
         )
         .subcommand(
@@ -118,10 +507,23 @@ fn main() -> Result<(), String> {
                 .clone()
                 .arg(&project_name_arg)
                 .arg(&deps_arg)
+                .arg(&args_arg)
                 .about("Removes dependencies from a project (alias: rm)"),
         )
+        .subcommand(install_subcommand)
         .subcommand(install_isolated_subcommand)
         .subcommand(workspace_subcommand)
+        .subcommand(graph_subcommand)
+        .subcommand(cycles_subcommand)
+        .subcommand(boundaries_subcommand)
+        .subcommand(check_subcommand)
+        .subcommand(list_subcommand)
+        .subcommand(optimize_subcommand)
+        .subcommand(size_subcommand)
+        .subcommand(sbom_subcommand)
+        .subcommand(licenses_subcommand)
+        .subcommand(generate_package_json_subcommand)
+        .subcommand(docker_subcommand)
         .subcommand(utils_subcommand)
         .get_matches();
 
@@ -133,30 +535,112 @@ fn main() -> Result<(), String> {
                 .unwrap()
                 .map(String::from)
                 .collect();
-            let is_development = sub_matches.is_present(DEVELOPMENT);
-            let skip_package_manager = sub_matches.is_present(SKIP);
+            let options = get_add_options(sub_matches, config);
             trace!("Project Name: {:?}", project_name);
             trace!("Dependencies to add: {:?}", to_add);
-            trace!("Development: {:?}", is_development);
-            trace!("Calling package manager: {:?}", !skip_package_manager);
-            perform_add(
-                project_name.to_owned(),
-                to_add,
-                is_development,
-                skip_package_manager,
-            )?
+            trace!("Options: {:?}", options);
+            perform_add(project_name.to_owned(), to_add, options)?
         }
         Some((INIT_COMMAND, _)) => perform_init()?,
+        Some((INSTALL_COMMAND, sub_matches)) => {
+            let is_frozen = is_enabled(
+                sub_matches,
+                FROZEN,
+                NO_FROZEN,
+                config.commands.install.frozen,
+            );
+            let is_production = is_enabled(
+                sub_matches,
+                PRODUCTION,
+                NO_PRODUCTION,
+                config.commands.install.production,
+            );
+            let extra_args = get_extra_args(sub_matches);
+            trace!("Frozen: {:?}", is_frozen);
+            trace!("Production: {:?}", is_production);
+            trace!("Extra arguments: {:?}", extra_args);
+            perform_install(is_frozen, is_production, extra_args)?
+        }
         Some((INSTALL_ISOLATED_COMMAND, sub_matches)) => {
             let project_names = sub_matches
                 .values_of(PROJECTS)
                 .unwrap()
                 .map(String::from)
                 .collect();
-            let call_script_executor = sub_matches.is_present(CALL_SCRIPT_EXECUTOR);
+            let call_script_executor = is_enabled(
+                sub_matches,
+                CALL_SCRIPT_EXECUTOR,
+                NO_CALL,
+                config.call_script_executor,
+            );
+            let use_cache = !is_enabled(
+                sub_matches,
+                NO_CACHE,
+                CACHE,
+                config.commands.install_isolated.no_cache,
+            );
+            let defaults = &config.commands.install_isolated;
+            let mut scopes = match sub_matches.values_of_t::<RequirementScope>(ONLY) {
+                Ok(scopes) => scopes.into_iter().collect(),
+                Err(_) => defaults
+                    .scopes
+                    .clone()
+                    .unwrap_or_else(RequirementScope::all),
+            };
+            scopes.extend(
+                sub_matches
+                    .values_of_t::<RequirementScope>(INCLUDE)
+                    .unwrap_or_default(),
+            );
+            let options = InstallIsolatedOptions {
+                scopes,
+                install: is_enabled(sub_matches, INSTALL, NO_INSTALL, defaults.install),
+                frozen: is_enabled(sub_matches, FROZEN, NO_FROZEN, defaults.frozen),
+                production: is_enabled(
+                    sub_matches,
+                    PRODUCTION,
+                    NO_PRODUCTION,
+                    defaults.production,
+                ),
+                restore_after: is_enabled(
+                    sub_matches,
+                    RESTORE_AFTER,
+                    NO_RESTORE_AFTER,
+                    defaults.restore_after,
+                ),
+                prune_overrides: is_enabled(
+                    sub_matches,
+                    PRUNE_OVERRIDES,
+                    NO_PRUNE_OVERRIDES,
+                    defaults.prune_overrides,
+                ),
+                without_graph: sub_matches.is_present(WITHOUT_GRAPH),
+                workspace_protocol: sub_matches
+                    .value_of_t::<WorkspaceProtocol>(WORKSPACE_PROTOCOL)
+                    .ok()
+                    .or(defaults.workspace_protocol)
+                    .unwrap_or_default(),
+                skip_implicit: is_enabled(
+                    sub_matches,
+                    SKIP_IMPLICIT,
+                    NO_SKIP_IMPLICIT,
+                    defaults.skip_implicit,
+                ),
+                targets: sub_matches
+                    .values_of(TARGET)
+                    .map(|targets| targets.map(String::from).collect()),
+                extra_args: get_extra_args(sub_matches),
+            };
             trace!("Call script executor: {:?}", call_script_executor);
+            trace!("Use cache: {:?}", use_cache);
             trace!("Target projects: {:?}", project_names);
-            perform_install_isolated(project_names, call_script_executor)?
+            trace!("Options: {:?}", options);
+            perform_install_isolated(
+                project_names,
+                call_script_executor,
+                use_cache,
+                options,
+            )?
         }
         Some((REMOVE_COMMAND, sub_matches)) => {
             let project_name = sub_matches.value_of(PROJECT_NAME).unwrap();
@@ -165,19 +649,364 @@ fn main() -> Result<(), String> {
                 .unwrap()
                 .map(String::from)
                 .collect();
+            let extra_args = get_extra_args(sub_matches);
             trace!("Project Name: {:?}", project_name);
             trace!("Dependencies to add: {:?}", to_remove);
-            perform_remove(project_name.to_owned(), to_remove)?
+            trace!("Extra arguments: {:?}", extra_args);
+            perform_remove(project_name.to_owned(), to_remove, extra_args)?
+        }
+        Some((GRAPH_COMMAND, sub_matches)) => {
+            let project_name = sub_matches.value_of(PROJECT_NAME).map(String::from);
+            let direction = sub_matches
+                .value_of_t::<Direction>(DIRECTION)
+                .ok()
+                .or(config.commands.graph.direction)
+                .unwrap_or(Direction::Both);
+            let format = sub_matches
+                .value_of_t::<GraphFormat>(FORMAT)
+                .ok()
+                .or(config.commands.graph.format)
+                .unwrap_or(GraphFormat::Dot);
+            let with_dependencies = is_enabled(
+                sub_matches,
+                WITH_DEPENDENCIES,
+                NO_WITH_DEPENDENCIES,
+                config.commands.graph.with_dependencies,
+            );
+            let call_script_executor = is_enabled(
+                sub_matches,
+                CALL_SCRIPT_EXECUTOR,
+                NO_CALL,
+                config.call_script_executor,
+            );
+            let use_cache =
+                !is_enabled(sub_matches, NO_CACHE, CACHE, config.commands.graph.no_cache);
+            trace!("Project Name: {:?}", project_name);
+            trace!("Direction: {:?}", direction);
+            trace!("Format: {:?}", format);
+            trace!("With dependencies: {:?}", with_dependencies);
+            trace!("Call script executor: {:?}", call_script_executor);
+            trace!("Use cache: {:?}", use_cache);
+            let graph = perform_graph(
+                project_name,
+                direction,
+                format,
+                with_dependencies,
+                call_script_executor,
+                use_cache,
+            )?;
+            print!("{graph}");
+        }
+        Some((CYCLES_COMMAND, sub_matches)) => {
+            let allowed = sub_matches
+                .values_of(ALLOW)
+                .unwrap_or_default()
+                .map(|cycle| cycle.split(',').map(|p| p.trim().to_owned()).collect())
+                .chain(config.commands.cycles.allow.iter().cloned())
+                .collect();
+            let call_script_executor = is_enabled(
+                sub_matches,
+                CALL_SCRIPT_EXECUTOR,
+                NO_CALL,
+                config.call_script_executor,
+            );
+            let use_cache = !is_enabled(
+                sub_matches,
+                NO_CACHE,
+                CACHE,
+                config.commands.cycles.no_cache,
+            );
+            trace!("Allowed cycles: {:?}", allowed);
+            trace!("Call script executor: {:?}", call_script_executor);
+            trace!("Use cache: {:?}", use_cache);
+            let cycles = perform_cycles(allowed, call_script_executor, use_cache)?;
+            if !cycles.is_empty() {
+                cycles.iter().for_each(|c| println!("{}", c.join(" -> ")));
+                return Err(format!("Found {} circular dependencies", cycles.len()));
+            }
+        }
+        Some((BOUNDARIES_COMMAND, sub_matches)) => {
+            let call_script_executor = is_enabled(
+                sub_matches,
+                CALL_SCRIPT_EXECUTOR,
+                NO_CALL,
+                config.call_script_executor,
+            );
+            let use_cache = !is_enabled(
+                sub_matches,
+                NO_CACHE,
+                CACHE,
+                config.commands.boundaries.no_cache,
+            );
+            trace!("Call script executor: {:?}", call_script_executor);
+            trace!("Use cache: {:?}", use_cache);
+            let violations = perform_boundaries(call_script_executor, use_cache)?;
+            if !violations.is_empty() {
+                violations.iter().for_each(|v| println!("{v}"));
+                return Err(format!("Found {} boundary violations", violations.len()));
+            }
+        }
+        Some((CHECK_COMMAND, sub_matches)) => {
+            let project_names = sub_matches
+                .values_of(PROJECTS)
+                .unwrap_or_default()
+                .map(String::from)
+                .collect();
+            trace!("Target projects: {:?}", project_names);
+            let missing = perform_check(project_names)?;
+            if !missing.is_empty() {
+                missing.iter().for_each(|m| println!("{m}"));
+                return Err(format!(
+                    "Found {} missing packages or groups",
+                    missing.len()
+                ));
+            }
+        }
+        Some((OPTIMIZE_COMMAND, sub_matches)) => {
+            let max_users = sub_matches
+                .value_of_t::<usize>(MAX_USERS)
+                .ok()
+                .or(config.commands.optimize.max_users)
+                .unwrap_or(DEFAULT_MAX_USERS);
+            let min_share = sub_matches
+                .value_of_t::<f64>(MIN_SHARE)
+                .ok()
+                .or(config.commands.optimize.min_share)
+                .unwrap_or(DEFAULT_MIN_SHARE);
+            let apply = sub_matches.is_present(APPLY);
+            trace!("Max users: {:?}", max_users);
+            trace!("Min share: {:?}", min_share);
+            trace!("Apply: {:?}", apply);
+            let suggestions = perform_optimize(max_users, min_share, apply)?;
+            match suggestions.is_empty() {
+                true => info!("Every dependency is already declared in the right place"),
+                false => suggestions.iter().for_each(|s| println!("{s}")),
+            }
+        }
+        Some((SIZE_COMMAND, sub_matches)) => {
+            let project_names = sub_matches
+                .values_of(PROJECTS)
+                .unwrap_or_default()
+                .map(String::from)
+                .collect();
+            let format = sub_matches
+                .value_of_t::<SizeFormat>(FORMAT)
+                .ok()
+                .or(config.commands.size.format)
+                .unwrap_or(SizeFormat::Table);
+            let top = sub_matches
+                .value_of_t::<usize>(TOP)
+                .ok()
+                .or(config.commands.size.top)
+                .unwrap_or(DEFAULT_TOP);
+            let call_script_executor = is_enabled(
+                sub_matches,
+                CALL_SCRIPT_EXECUTOR,
+                NO_CALL,
+                config.call_script_executor,
+            );
+            let use_cache =
+                !is_enabled(sub_matches, NO_CACHE, CACHE, config.commands.size.no_cache);
+            trace!("Target projects: {:?}", project_names);
+            trace!("Format: {:?}", format);
+            trace!("Top: {:?}", top);
+            trace!("Call script executor: {:?}", call_script_executor);
+            trace!("Use cache: {:?}", use_cache);
+            let report = perform_size(
+                project_names,
+                format,
+                top,
+                call_script_executor,
+                use_cache,
+            )?;
+            print!("{report}");
+        }
+        Some((LIST_COMMAND, sub_matches)) => {
+            let project_name = sub_matches.value_of(PROJECT_NAME).map(String::from);
+            trace!("Project Name: {:?}", project_name);
+            perform_list(project_name)?
+                .iter()
+                .for_each(|line| println!("{line}"));
+        }
+        Some((GENERATE_PACKAGE_JSON_COMMAND, sub_matches)) => {
+            let project_name = sub_matches.value_of(PROJECT_NAME).unwrap();
+            let output = sub_matches.value_of(OUTPUT).map(PathBuf::from);
+            let use_caret = is_enabled(
+                sub_matches,
+                CARET,
+                NO_CARET,
+                config.commands.generate_package_json.caret,
+            );
+            let call_script_executor = is_enabled(
+                sub_matches,
+                CALL_SCRIPT_EXECUTOR,
+                NO_CALL,
+                config.call_script_executor,
+            );
+            let use_cache = !is_enabled(
+                sub_matches,
+                NO_CACHE,
+                CACHE,
+                config.commands.generate_package_json.no_cache,
+            );
+            trace!("Project Name: {:?}", project_name);
+            trace!("Output: {:?}", output);
+            trace!("Caret: {:?}", use_caret);
+            trace!("Call script executor: {:?}", call_script_executor);
+            trace!("Use cache: {:?}", use_cache);
+            perform_generate_package_json(
+                project_name.to_owned(),
+                output,
+                use_caret,
+                call_script_executor,
+                use_cache,
+            )?;
+        }
+        Some((LICENSES_COMMAND, sub_matches)) => {
+            let project_names = sub_matches
+                .values_of(PROJECTS)
+                .unwrap_or_default()
+                .map(String::from)
+                .collect();
+            let production = is_enabled(
+                sub_matches,
+                PRODUCTION,
+                NO_PRODUCTION,
+                config.commands.licenses.production,
+            );
+            let call_script_executor = is_enabled(
+                sub_matches,
+                CALL_SCRIPT_EXECUTOR,
+                NO_CALL,
+                config.call_script_executor,
+            );
+            let use_cache = !is_enabled(
+                sub_matches,
+                NO_CACHE,
+                CACHE,
+                config.commands.licenses.no_cache,
+            );
+            trace!("Target projects: {:?}", project_names);
+            trace!("Production: {:?}", production);
+            trace!("Call script executor: {:?}", call_script_executor);
+            trace!("Use cache: {:?}", use_cache);
+            let violations = perform_licenses(
+                project_names,
+                production,
+                call_script_executor,
+                use_cache,
+            )?;
+            if !violations.is_empty() {
+                violations.iter().for_each(|v| println!("{v}"));
+                return Err(format!("Found {} license violations", violations.len()));
+            }
+        }
+        Some((SBOM_COMMAND, sub_matches)) => {
+            let project_name = sub_matches.value_of(PROJECT_NAME).unwrap();
+            let format = sub_matches
+                .value_of_t::<SbomFormat>(FORMAT)
+                .ok()
+                .or(config.commands.sbom.format)
+                .unwrap_or(SbomFormat::CycloneDx);
+            let output = sub_matches.value_of(OUTPUT).map(PathBuf::from);
+            let is_production = is_enabled(
+                sub_matches,
+                PRODUCTION,
+                NO_PRODUCTION,
+                config.commands.sbom.production,
+            );
+            let call_script_executor = is_enabled(
+                sub_matches,
+                CALL_SCRIPT_EXECUTOR,
+                NO_CALL,
+                config.call_script_executor,
+            );
+            let use_cache =
+                !is_enabled(sub_matches, NO_CACHE, CACHE, config.commands.sbom.no_cache);
+            trace!("Project Name: {:?}", project_name);
+            trace!("Format: {:?}", format);
+            trace!("Output: {:?}", output);
+            trace!("Production: {:?}", is_production);
+            trace!("Call script executor: {:?}", call_script_executor);
+            trace!("Use cache: {:?}", use_cache);
+            let sbom = perform_sbom(
+                project_name.to_owned(),
+                format,
+                is_production,
+                call_script_executor,
+                use_cache,
+            )?;
+            match output {
+                Some(output) => {
+                    write(&output, sbom)
+                        .map_err(|err| format!("Could not write {output:?}: {err}"))?;
+                    info!("Wrote the bill of materials to {output:?}");
+                }
+                None => print!("{sbom}"),
+            }
+        }
+        Some((DOCKER_COMMAND, sub_matches)) => {
+            let project_name = sub_matches.value_of(PROJECT_NAME).unwrap();
+            let output = sub_matches.value_of(OUTPUT).map(PathBuf::from);
+            let base_image = sub_matches
+                .value_of(BASE_IMAGE)
+                .map(String::from)
+                .or_else(|| config.commands.docker.base_image.clone())
+                .unwrap_or_else(|| DEFAULT_BASE_IMAGE.to_owned());
+            let call_script_executor = is_enabled(
+                sub_matches,
+                CALL_SCRIPT_EXECUTOR,
+                NO_CALL,
+                config.call_script_executor,
+            );
+            let use_cache = !is_enabled(
+                sub_matches,
+                NO_CACHE,
+                CACHE,
+                config.commands.docker.no_cache,
+            );
+            trace!("Project Name: {:?}", project_name);
+            trace!("Output: {:?}", output);
+            trace!("Base image: {:?}", base_image);
+            trace!("Call script executor: {:?}", call_script_executor);
+            trace!("Use cache: {:?}", use_cache);
+            perform_docker(
+                project_name.to_owned(),
+                output,
+                base_image,
+                call_script_executor,
+                use_cache,
+            )?;
         }
         Some((UTILS_SUBCOMMAND, matches)) => match matches.subcommand() {
             Some((GET_DEPENDENCIES_COMMAND, sub_matches)) => {
                 let project_name = sub_matches.value_of(PROJECT_NAME).unwrap();
-                let call_script_executor = sub_matches.is_present(CALL_SCRIPT_EXECUTOR);
+                let call_script_executor = is_enabled(
+                    sub_matches,
+                    CALL_SCRIPT_EXECUTOR,
+                    NO_CALL,
+                    config.call_script_executor,
+                );
+                let direction = sub_matches
+                    .value_of_t::<Direction>(DIRECTION)
+                    .ok()
+                    .or(config.commands.get_dependencies.direction)
+                    .unwrap_or(Direction::Both);
+                let use_cache = !is_enabled(
+                    sub_matches,
+                    NO_CACHE,
+                    CACHE,
+                    config.commands.get_dependencies.no_cache,
+                );
                 trace!("Project Name: {:?}", project_name);
                 trace!("Call script executor: {:?}", call_script_executor);
+                trace!("Direction: {:?}", direction);
+                trace!("Use cache: {:?}", use_cache);
                 match utils_get_dependencies(
                     project_name.to_owned(),
                     call_script_executor,
+                    direction,
+                    use_cache,
                 ) {
                     Ok(project_names) => print!("{}", project_names.join(" ")),
                     Err(err) => error!("Encountered an error: {err:?}"),
@@ -192,12 +1021,10 @@ fn main() -> Result<(), String> {
                     .unwrap()
                     .map(String::from)
                     .collect();
-                let is_development = sub_matches.is_present(DEVELOPMENT);
-                let skip_package_manager = sub_matches.is_present(SKIP);
+                let options = get_add_options(sub_matches, config);
                 trace!("Dependencies to add: {:?}", to_add);
-                trace!("Development: {:?}", is_development);
-                trace!("Calling package manager: {:?}", !skip_package_manager);
-                perform_workspace_add(to_add, is_development, skip_package_manager)?
+                trace!("Options: {:?}", options);
+                perform_workspace_add(to_add, options)?
             }
             Some((REMOVE_COMMAND, sub_matches)) => {
                 let to_remove = sub_matches
@@ -205,8 +1032,10 @@ fn main() -> Result<(), String> {
                     .unwrap()
                     .map(String::from)
                     .collect();
+                let extra_args = get_extra_args(sub_matches);
                 trace!("Dependencies to remove: {:?}", to_remove);
-                perform_workspace_remove(to_remove)?
+                trace!("Extra arguments: {:?}", extra_args);
+                perform_workspace_remove(to_remove, extra_args)?
             }
             _ => unreachable!(),
         },
@@ -214,3 +1043,93 @@ fn main() -> Result<(), String> {
     }
     Ok(())
 }
+
+/// the flag that turns off `flag` when it is enabled in the configuration. Clap applies
+/// the override both ways, so whichever of the two is passed last wins.
+fn negation<'a>(flag: &'a str, name: &'a str, help: &'a str) -> Arg<'a> {
+    Arg::new(name).long(name).overrides_with(flag).help(help)
+}
+
+/// whether `flag` is enabled: passing it (or its `negation`) on the command line takes
+/// precedence over the `default` of the configuration
+fn is_enabled(matches: &ArgMatches, flag: &str, negation: &str, default: bool) -> bool {
+    match (matches.is_present(flag), matches.is_present(negation)) {
+        (true, _) => true,
+        (_, true) => false,
+        _ => default,
+    }
+}
+
+/// the requirement scope selected by the `-D`, `-P` and `-O` flags of `add`
+fn get_requirement_scope(matches: &ArgMatches) -> RequirementScope {
+    // target dependencies are development dependencies of the package manager
+    if matches.is_present(DEVELOPMENT) || matches.is_present(TARGET) {
+        RequirementScope::Development
+    } else if matches.is_present(PEER) {
+        RequirementScope::Peer
+    } else if matches.is_present(OPTIONAL) {
+        RequirementScope::Optional
+    } else {
+        RequirementScope::Required
+    }
+}
+
+/// the options of `add` and `workspace add`, falling back to `commands.add` of the
+/// configuration
+fn get_add_options(matches: &ArgMatches, config: &EsteemConfig) -> AddOptions {
+    AddOptions {
+        scope: get_requirement_scope(matches),
+        target: matches.value_of(TARGET).map(String::from),
+        is_exact: is_enabled(matches, EXACT, NO_EXACT, config.commands.add.exact),
+        skip_package_manager: is_enabled(
+            matches,
+            SKIP,
+            NO_SKIP,
+            config.commands.add.skip,
+        ),
+        with_types: is_enabled(matches, TYPES, NO_TYPES, config.commands.add.types),
+        extra_args: get_extra_args(matches),
+    }
+}
+
+/// the arguments passed after `--`, which are forwarded to the package manager
+fn get_extra_args(matches: &ArgMatches) -> Vec<String> {
+    matches
+        .values_of(ARGS)
+        .unwrap_or_default()
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_frozen(args: &[&str], default: bool) -> bool {
+        let app = App::new("test")
+            .arg(arg!(--frozen))
+            .arg(negation(FROZEN, NO_FROZEN, ""));
+        let matches = app.get_matches_from([&["test"], args].concat());
+        is_enabled(&matches, FROZEN, NO_FROZEN, default)
+    }
+
+    #[test]
+    fn uses_the_configuration_without_flags() {
+        assert!(is_frozen(&[], true));
+        assert!(!is_frozen(&[], false));
+    }
+
+    #[test]
+    fn prefers_the_flags_to_the_configuration() {
+        assert!(is_frozen(&["--frozen"], false));
+        assert!(!is_frozen(&["--no-frozen"], true));
+    }
+
+    #[test]
+    fn prefers_the_last_of_the_flag_and_its_negation() {
+        assert!(!is_frozen(&["--frozen", "--no-frozen"], false));
+        assert!(is_frozen(&["--no-frozen", "--frozen"], false));
+        assert!(!is_frozen(&["--frozen", "--no-frozen"], true));
+        assert!(is_frozen(&["--no-frozen", "--frozen"], true));
+    }
+}
@@ -1,19 +1,36 @@
-use super::LibraryError;
+use super::{
+    config::EsteemConfig,
+    constants::{PACKAGE_JSON_FILE, YARNRC_FILE},
+    dependencies::RequirementScope,
+    LibraryError,
+};
 use duct::{cmd, Expression};
 use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::Value;
 use std::{
     env::current_dir,
-    fs::read_dir,
+    fs::{read_dir, read_to_string},
     io::{BufRead, BufReader},
-    path::PathBuf,
+    path::Path,
 };
-use tempfile::NamedTempFile;
+
+/// The placeholder in a command template that is replaced by the packages
+const PACKAGES_PLACEHOLDER: &str = "{packages}";
+/// The placeholder in a command template that is replaced by the flag of the requirement
+/// scope that dependencies are added to, nothing for required dependencies
+const DEV_PLACEHOLDER: &str = "{dev}";
+/// The placeholder in a command template that is replaced by the production flag when
+/// only required dependencies are installed
+const PROD_PLACEHOLDER: &str = "{prod}";
+/// The placeholder in a command template that is replaced by the extra arguments
+const ARGS_PLACEHOLDER: &str = "{args}";
 
 #[derive(Debug)]
 pub struct CommandExecutor<'a> {
-    /// the command that has to be executed
+    /// the command that has to be executed, starting with the program
     command_to_execute: Vec<String>,
-    /// whether to directly call the command executor without using `script_executor`
+    /// whether to call NX using the `exec` template of the package manager
     call_script_executor: bool,
     /// the package manager configuration for this command
     package_manager: &'a PackageManager,
@@ -28,114 +45,356 @@ impl CommandExecutor<'_> {
         }
     }
 
-    pub fn add_required_dependencies(&mut self, to_add: Vec<String>) {
-        self.start_install_command();
-        self.push_dependencies_args(to_add);
+    /// adds packages using the `add` template, or `addExact` if `is_exact` is set
+    pub fn add_dependencies(
+        &mut self,
+        to_add: Vec<String>,
+        scope: RequirementScope,
+        is_exact: bool,
+        extra_args: Vec<String>,
+    ) -> Result<(), LibraryError> {
+        let template = match is_exact {
+            true => self.package_manager.get(|m| &m.add_exact, "addExact")?,
+            false => self.package_manager.get(|m| &m.add, "add")?,
+        };
+        let manager = self.package_manager;
+        let scope_flag = match scope {
+            RequirementScope::Required => None,
+            RequirementScope::Development => {
+                Some(manager.get(|m| &m.development_flag, "developmentFlag")?)
+            }
+            RequirementScope::Peer => Some(manager.get(|m| &m.peer_flag, "peerFlag")?),
+            RequirementScope::Optional => {
+                Some(manager.get(|m| &m.optional_flag, "optionalFlag")?)
+            }
+        };
+        self.command_to_execute = expand_template(
+            template,
+            &to_add,
+            scope_flag.map(|flag| (DEV_PLACEHOLDER, flag)),
+            &extra_args,
+        );
+        Ok(())
     }
 
-    pub fn add_development_dependencies(&mut self, to_add: Vec<String>) {
-        self.start_install_command();
-        self.command_to_execute
-            .push(self.package_manager.development_flag.clone());
-        self.push_dependencies_args(to_add);
+    pub fn remove_dependencies(
+        &mut self,
+        to_remove: Vec<String>,
+        extra_args: Vec<String>,
+    ) -> Result<(), LibraryError> {
+        let template = self.package_manager.get(|m| &m.remove, "remove")?;
+        self.command_to_execute =
+            expand_template(template, &to_remove, None, &extra_args);
+        Ok(())
     }
 
-    fn start_install_command(&mut self) {
-        self.command_to_execute
-            .push(self.package_manager.install.clone());
+    /// installs every package of `package.json`, using the `installFrozen` template if
+    /// the lockfile should not be updated and skipping development dependencies if
+    /// `is_production` is set
+    pub fn install_dependencies(
+        &mut self,
+        is_frozen: bool,
+        is_production: bool,
+        extra_args: Vec<String>,
+    ) -> Result<(), LibraryError> {
+        let template = match is_frozen {
+            true => self
+                .package_manager
+                .get(|m| &m.install_frozen, "installFrozen")?,
+            false => self.package_manager.get(|m| &m.install_all, "installAll")?,
+        };
+        let production_flag = match is_production {
+            true => Some((
+                PROD_PLACEHOLDER,
+                self.package_manager
+                    .get(|m| &m.production_flag, "productionFlag")
+                    .map_err(|err| {
+                        LibraryError(format!(
+                            "The package manager can not install only the required dependencies: {}",
+                            err.0
+                        ))
+                    })?,
+            )),
+            false => None,
+        };
+        self.command_to_execute =
+            expand_template(template, &[], production_flag, &extra_args);
+        Ok(())
     }
 
-    fn push_dependencies_args(&mut self, to_add: Vec<String>) {
-        to_add.into_iter().for_each(|f| {
-            self.command_to_execute.push(f);
-        });
+    /// writes the NX project graph to `file`, focused on `project_name` if it is given
+    pub fn graph_dependencies(
+        &mut self,
+        project_name: Option<&String>,
+        file: &Path,
+    ) -> Result<(), LibraryError> {
+        let mut args = vec![
+            "nx".into(),
+            "graph".into(),
+            "--file".into(),
+            file.to_string_lossy().to_string(),
+        ];
+        if let Some(project_name) = project_name {
+            args.extend(["--focus".into(), project_name.to_owned()]);
+        }
+        self.run_binary(args)
     }
 
-    pub fn remove_dependencies(&mut self, to_remove: Vec<String>) {
-        self.command_to_execute
-            .push(self.package_manager.remove.clone());
-        to_remove.into_iter().for_each(|f| {
-            self.command_to_execute.push(f);
-        });
+    /// runs a binary of the workspace, using the `exec` template if the script executor
+    /// should be called
+    pub fn run_binary(&mut self, args: Vec<String>) -> Result<(), LibraryError> {
+        self.command_to_execute = match self.call_script_executor {
+            true => {
+                let template = self.package_manager.get(|m| &m.exec, "exec")?;
+                expand_template(template, &[], None, &args)
+            }
+            false => args,
+        };
+        Ok(())
     }
 
-    pub fn graph_dependencies(&mut self, project_name: &String) -> PathBuf {
-        let file = NamedTempFile::new().unwrap();
-        let path = format!("{}.json", file.path().as_os_str().to_str().unwrap());
-        self.command_to_execute.extend([
-            "nx".into(),
-            "graph".into(),
-            "--file".into(),
-            path.clone(),
-            "--focus".into(),
-            project_name.to_owned(),
-        ]);
-        PathBuf::from(&path)
+    /// the command that would be executed, as it would be typed in a shell
+    pub fn command_line(&self) -> String {
+        shell_words::join(&self.command_to_execute)
     }
 
-    pub fn execute_command(self) {
-        let command = cmd(
-            &self.package_manager.command_executor,
-            &self.command_to_execute,
-        );
-        self.execute(command);
+    pub fn execute_command(self) -> Result<(), LibraryError> {
+        let command = self.expression();
+        self.execute(command)
     }
 
-    pub fn execute_script(self) {
-        let command = match self.call_script_executor {
-            true => cmd(
-                &self.package_manager.script_executor,
-                &self.command_to_execute,
-            ),
-            false => {
-                let program = self.command_to_execute[..1].get(0).unwrap();
-                let args = &self.command_to_execute[1..];
-                cmd(program, args)
-            }
-        };
-        self.execute(command);
+    /// executes the script without printing its output, which is returned instead. The
+    /// error contains the output of the script if it did not exit successfully.
+    pub fn execute_script_captured(self) -> Result<String, LibraryError> {
+        let command = self.expression();
+        info!("Calling command: {command:?}");
+        let output = command
+            .stdout_capture()
+            .stderr_capture()
+            .unchecked()
+            .run()
+            .map_err(|err| LibraryError(format!("Could not call {command:?}: {err}")))?;
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if output.status.success() {
+            Ok(stdout)
+        } else {
+            Err(LibraryError(format!(
+                "{command:?} exited with {}:\n{}{}",
+                output.status, stdout, stderr
+            )))
+        }
+    }
+
+    fn expression(&self) -> Expression {
+        let program = self.command_to_execute.first().unwrap();
+        let args = &self.command_to_execute[1..];
+        cmd(program, args)
     }
 
-    fn execute(self, command: Expression) {
+    fn execute(self, command: Expression) -> Result<(), LibraryError> {
         info!("Calling command: {command:?}");
-        let reader = command.stderr_to_stdout().reader().unwrap();
+        let reader = command
+            .stderr_to_stdout()
+            .reader()
+            .map_err(|err| LibraryError(format!("Could not call {command:?}: {err}")))?;
         let lines = BufReader::new(reader).lines();
         for line in lines {
-            println!("{}", line.unwrap());
+            let line = line.map_err(|err| LibraryError(err.to_string()))?;
+            println!("{line}");
         }
+        Ok(())
     }
 }
 
-#[derive(Clone, Debug)]
+/// Splits a command template into words like a shell would, so that quoted arguments can
+/// contain whitespace, and replaces its placeholders. A placeholder has to be a whole word
+/// of the template, placeholders that expand to nothing are dropped. `flag` is the
+/// placeholder of a flag along with its value.
+fn expand_template(
+    template: &str,
+    packages: &[String],
+    flag: Option<(&str, &str)>,
+    extra_args: &[String],
+) -> Vec<String> {
+    // the templates are validated when the configuration is loaded
+    split_template(template)
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|word| match word.as_str() {
+            PACKAGES_PLACEHOLDER => packages.to_vec(),
+            DEV_PLACEHOLDER | PROD_PLACEHOLDER => flag
+                .filter(|(placeholder, _)| *placeholder == word)
+                .map(|(_, value)| value.to_owned())
+                .into_iter()
+                .collect(),
+            ARGS_PLACEHOLDER => extra_args.to_vec(),
+            _ => vec![word],
+        })
+        .collect()
+}
+
+/// splits a command template into its words, respecting quotes and escapes
+fn split_template(template: &str) -> Result<Vec<String>, LibraryError> {
+    shell_words::split(template)
+        .map_err(|err| LibraryError(format!("Could not parse {template:?}: {err}")))
+}
+
+/// Selects one of the commands (or flags) of a package manager
+type Selector = fn(&PackageManager) -> &Option<String>;
+
+/// The commands used to drive a package manager. Each command is a template in which
+/// `{packages}`, `{dev}`, `{prod}` and `{args}` are replaced by the packages, the flag of
+/// the requirement scope, the production flag and the extra arguments passed to esteem.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
 pub struct PackageManager {
-    /// the installation subcommand of the package manager
-    install: String,
-    /// the remove subcommand of the package manager
-    remove: String,
-    /// the script execution binary
-    script_executor: String,
-    /// the command that executes normal scripts
-    command_executor: String,
-    /// the flag to use while adding development dependency
-    development_flag: String,
+    /// one of the built-in package managers whose commands are used for the ones that
+    /// are not defined
+    extends: Option<String>,
+    /// adds packages, eg: `pnpm add {dev} {packages} {args}`
+    add: Option<String>,
+    /// adds packages with their exact version instead of a range
+    add_exact: Option<String>,
+    /// removes packages
+    remove: Option<String>,
+    /// installs every package of `package.json`
+    install_all: Option<String>,
+    /// installs every package of `package.json` without updating the lockfile
+    install_frozen: Option<String>,
+    /// runs a binary of the workspace, used to call NX
+    exec: Option<String>,
+    /// the flag that `{dev}` is replaced with while adding development dependencies
+    development_flag: Option<String>,
+    /// the flag that `{dev}` is replaced with while adding peer dependencies
+    peer_flag: Option<String>,
+    /// the flag that `{dev}` is replaced with while adding optional dependencies
+    optional_flag: Option<String>,
+    /// the flag that `{prod}` is replaced with while installing only required
+    /// dependencies
+    production_flag: Option<String>,
 }
 
 impl PackageManager {
+    /// checks that every command of a custom package manager can be resolved and only
+    /// uses known placeholders
+    pub fn validate(&self, name: &str) -> Result<(), LibraryError> {
+        if let Some(extends) = &self.extends {
+            if Self::builtin(extends).is_none() {
+                return Err(LibraryError(format!(
+                    "Package manager {name:?} extends {extends:?}, which must be one of `npm`, `yarn` or `pnpm`"
+                )));
+            }
+        }
+        let templates: [(Selector, &str); 6] = [
+            (|m| &m.add, "add"),
+            (|m| &m.add_exact, "addExact"),
+            (|m| &m.remove, "remove"),
+            (|m| &m.install_all, "installAll"),
+            (|m| &m.install_frozen, "installFrozen"),
+            (|m| &m.exec, "exec"),
+        ];
+        for (select, key) in templates {
+            let template = self.get(select, key).map_err(|err| {
+                LibraryError(format!("Package manager {name:?}: {}", err.0))
+            })?;
+            let words = split_template(template).map_err(|err| {
+                LibraryError(format!("Package manager {name:?}: {} in `{key}`", err.0))
+            })?;
+            if words.is_empty() {
+                return Err(LibraryError(format!(
+                    "Package manager {name:?}: `{key}` can not be empty"
+                )));
+            }
+            let unknown = words.iter().find(|word| {
+                word.starts_with('{')
+                    && word.ends_with('}')
+                    && ![
+                        PACKAGES_PLACEHOLDER,
+                        DEV_PLACEHOLDER,
+                        PROD_PLACEHOLDER,
+                        ARGS_PLACEHOLDER,
+                    ]
+                    .contains(&word.as_str())
+            });
+            if let Some(unknown) = unknown {
+                return Err(LibraryError(format!(
+                    "Package manager {name:?}: unknown placeholder {unknown:?} in `{key}`"
+                )));
+            }
+        }
+        let flags: [(Selector, &str); 4] = [
+            (|m| &m.development_flag, "developmentFlag"),
+            (|m| &m.peer_flag, "peerFlag"),
+            (|m| &m.optional_flag, "optionalFlag"),
+            (|m| &m.production_flag, "productionFlag"),
+        ];
+        for (select, key) in flags {
+            self.get(select, key).map_err(|err| {
+                LibraryError(format!("Package manager {name:?}: {}", err.0))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// returns the value of a command (or the development flag) of this package manager,
+    /// falling back to the one of the package manager it extends
+    fn get(&self, select: Selector, key: &str) -> Result<&str, LibraryError> {
+        if let Some(value) = select(self) {
+            return Ok(value);
+        }
+        match self.extends.as_deref().and_then(Self::builtin) {
+            Some(fallback) => fallback.get(select, key),
+            None => Err(LibraryError(format!(
+                "`{key}` is not defined and no package manager is extended"
+            ))),
+        }
+    }
+
+    /// returns one of the package managers that esteem supports out of the box, `yarn`
+    /// is yarn 2+ if the workspace uses it
+    pub fn builtin(name: &str) -> Option<&'static Self> {
+        match name {
+            "npm" => Some(&NPM_PACKAGE_MANAGER),
+            "pnpm" => Some(&PNPM_PACKAGE_MANAGER),
+            "yarn" => match is_yarn_berry(&current_dir().unwrap()) {
+                true => Some(&YARN_BERRY_PACKAGE_MANAGER),
+                false => Some(&YARN_PACKAGE_MANAGER),
+            },
+            _ => None,
+        }
+    }
+
+    /// returns an executor for the package manager set in the configuration, or guesses
+    /// the package manager from the lockfile present in the current directory
     pub fn get_command_executor(
         call_script_executor: bool,
     ) -> Result<CommandExecutor<'static>, LibraryError> {
+        let config = EsteemConfig::get();
+        if let Some(name) = &config.package_manager {
+            let manager = Self::builtin(name)
+                .or_else(|| config.managers.get(name))
+                .ok_or_else(|| {
+                    LibraryError(format!("Unknown package manager {name:?}"))
+                })?;
+            return Ok(CommandExecutor::new(call_script_executor, manager));
+        }
         let dir = read_dir(current_dir().unwrap()).unwrap();
         for file in dir {
-            let executor = match file.unwrap().file_name().to_os_string().to_str().unwrap() {
-                "yarn.lock" => CommandExecutor::new(call_script_executor, &YARN_PACKAGE_MANAGER),
-                "pnpm-lock.yaml" => {
-                    CommandExecutor::new(call_script_executor, &PNPM_PACKAGE_MANAGER)
-                }
-                "package-lock.json" => {
-                    CommandExecutor::new(call_script_executor, &NPM_PACKAGE_MANAGER)
-                }
-                _ => continue,
-            };
+            let executor =
+                match file.unwrap().file_name().to_os_string().to_str().unwrap() {
+                    "yarn.lock" => CommandExecutor::new(
+                        call_script_executor,
+                        Self::builtin("yarn").unwrap(),
+                    ),
+                    "pnpm-lock.yaml" => {
+                        CommandExecutor::new(call_script_executor, &PNPM_PACKAGE_MANAGER)
+                    }
+                    "package-lock.json" => {
+                        CommandExecutor::new(call_script_executor, &NPM_PACKAGE_MANAGER)
+                    }
+                    _ => continue,
+                };
             return Ok(executor);
         }
         Err(LibraryError("Could not guess an appropriate NPM package manager. Only `NPM`, `YARN` and `PNPM` are supported. Please open an issue in the repository if you would like to see any other manager supported.".to_owned()))
@@ -143,25 +402,243 @@ impl PackageManager {
 }
 
 static NPM_PACKAGE_MANAGER: Lazy<PackageManager> = Lazy::new(|| PackageManager {
-    install: "install".into(),
-    remove: "uninstall".into(),
-    script_executor: "npx".into(),
-    command_executor: "npm".into(),
-    development_flag: "--save-dev".into(),
+    extends: None,
+    add: Some("npm install {dev} {packages} {args}".into()),
+    add_exact: Some("npm install --save-exact {dev} {packages} {args}".into()),
+    remove: Some("npm uninstall {packages} {args}".into()),
+    install_all: Some("npm install {prod} {args}".into()),
+    install_frozen: Some("npm ci {prod} {args}".into()),
+    exec: Some("npx {args}".into()),
+    development_flag: Some("--save-dev".into()),
+    peer_flag: Some("--save-peer".into()),
+    optional_flag: Some("--save-optional".into()),
+    production_flag: Some("--omit=dev".into()),
 });
 
 static PNPM_PACKAGE_MANAGER: Lazy<PackageManager> = Lazy::new(|| PackageManager {
-    install: "install".into(),
-    remove: "remove".into(),
-    script_executor: "pnpm".into(),
-    command_executor: "pnpm".into(),
-    development_flag: "--save-dev".into(),
+    extends: None,
+    add: Some("pnpm install {dev} {packages} {args}".into()),
+    add_exact: Some("pnpm install --save-exact {dev} {packages} {args}".into()),
+    remove: Some("pnpm remove {packages} {args}".into()),
+    install_all: Some("pnpm install {prod} {args}".into()),
+    install_frozen: Some("pnpm install --frozen-lockfile {prod} {args}".into()),
+    exec: Some("pnpm {args}".into()),
+    development_flag: Some("--save-dev".into()),
+    peer_flag: Some("--save-peer".into()),
+    optional_flag: Some("--save-optional".into()),
+    production_flag: Some("--prod".into()),
 });
 
 static YARN_PACKAGE_MANAGER: Lazy<PackageManager> = Lazy::new(|| PackageManager {
-    install: "add".into(),
-    remove: "remove".into(),
-    script_executor: "yarn".into(),
-    command_executor: "yarn".into(),
-    development_flag: "--dev".into(),
+    extends: None,
+    add: Some("yarn add {dev} {packages} {args}".into()),
+    add_exact: Some("yarn add --exact {dev} {packages} {args}".into()),
+    remove: Some("yarn remove {packages} {args}".into()),
+    install_all: Some("yarn install {prod} {args}".into()),
+    install_frozen: Some("yarn install --frozen-lockfile {prod} {args}".into()),
+    exec: Some("yarn {args}".into()),
+    development_flag: Some("--dev".into()),
+    peer_flag: Some("--peer".into()),
+    optional_flag: Some("--optional".into()),
+    production_flag: Some("--production".into()),
+});
+
+/// Yarn 2+ can not skip the development dependencies while installing, so it has no
+/// production flag
+static YARN_BERRY_PACKAGE_MANAGER: Lazy<PackageManager> = Lazy::new(|| PackageManager {
+    extends: None,
+    add: Some("yarn add {dev} {packages} {args}".into()),
+    add_exact: Some("yarn add --exact {dev} {packages} {args}".into()),
+    remove: Some("yarn remove {packages} {args}".into()),
+    install_all: Some("yarn install {args}".into()),
+    install_frozen: Some("yarn install --immutable {args}".into()),
+    exec: Some("yarn {args}".into()),
+    development_flag: Some("--dev".into()),
+    peer_flag: Some("--peer".into()),
+    optional_flag: Some("--optional".into()),
+    production_flag: None,
 });
+
+/// whether the workspace in `directory` uses yarn 2+, which has a `.yarnrc.yml` or a
+/// `packageManager` field like `yarn@3.2.0` in its `package.json`
+fn is_yarn_berry(directory: &Path) -> bool {
+    if directory.join(YARNRC_FILE).exists() {
+        return true;
+    }
+    let manifest = read_to_string(directory.join(PACKAGE_JSON_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_str::<Value>(&data).ok());
+    manifest
+        .as_ref()
+        .and_then(|manifest| manifest.get("packageManager")?.as_str())
+        .and_then(|manager| manager.strip_prefix("yarn@"))
+        .and_then(|version| version.split('.').next()?.parse::<u32>().ok())
+        .is_some_and(|major| major >= 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+    use tempfile::tempdir;
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn expands_the_placeholders() {
+        let packages = strings(&["react", "@types/node"]);
+        let args = strings(&["--ignore-scripts"]);
+        assert_eq!(
+            expand_template(
+                "pnpm install {dev} {packages} {args}",
+                &packages,
+                Some((DEV_PLACEHOLDER, "--save-dev")),
+                &args
+            ),
+            strings(&[
+                "pnpm",
+                "install",
+                "--save-dev",
+                "react",
+                "@types/node",
+                "--ignore-scripts"
+            ])
+        );
+        assert_eq!(
+            expand_template(
+                "npm ci {prod} {args}",
+                &[],
+                Some((PROD_PLACEHOLDER, "--omit=dev")),
+                &[]
+            ),
+            strings(&["npm", "ci", "--omit=dev"])
+        );
+    }
+
+    #[test]
+    fn drops_the_placeholders_that_expand_to_nothing() {
+        assert_eq!(
+            expand_template(
+                "yarn add {dev} {packages} {args}",
+                &strings(&["a"]),
+                None,
+                &[]
+            ),
+            strings(&["yarn", "add", "a"])
+        );
+        // the flag only replaces its own placeholder
+        assert_eq!(
+            expand_template(
+                "yarn install {prod} {dev}",
+                &[],
+                Some((DEV_PLACEHOLDER, "--dev")),
+                &[]
+            ),
+            strings(&["yarn", "install", "--dev"])
+        );
+    }
+
+    #[test]
+    fn splits_templates_like_a_shell() {
+        assert_eq!(
+            expand_template(
+                "pnpm --filter \"my app\" add '{packages}' {packages}",
+                &strings(&["a", "b"]),
+                None,
+                &[]
+            ),
+            strings(&["pnpm", "--filter", "my app", "add", "a", "b", "a", "b"])
+        );
+        assert_eq!(
+            expand_template(
+                "pnpm x{packages} --filter=my\\ app",
+                &strings(&["a"]),
+                None,
+                &[]
+            ),
+            strings(&["pnpm", "x{packages}", "--filter=my app"])
+        );
+        assert!(split_template("pnpm \"add").is_err());
+    }
+
+    #[test]
+    fn quotes_the_command_line() {
+        let mut executor = CommandExecutor::new(false, &PNPM_PACKAGE_MANAGER);
+        executor
+            .add_dependencies(
+                strings(&["react"]),
+                RequirementScope::Development,
+                true,
+                strings(&["--filter", "my app", "$HOME"]),
+            )
+            .unwrap();
+        assert_eq!(
+            executor.command_line(),
+            "pnpm install --save-exact --save-dev react --filter 'my app' '$HOME'"
+        );
+    }
+
+    #[test]
+    fn uses_the_flags_of_the_scopes() {
+        for (scope, expected) in [
+            (RequirementScope::Required, "yarn add react"),
+            (RequirementScope::Development, "yarn add --dev react"),
+            (RequirementScope::Peer, "yarn add --peer react"),
+            (RequirementScope::Optional, "yarn add --optional react"),
+        ] {
+            let mut executor = CommandExecutor::new(false, &YARN_PACKAGE_MANAGER);
+            executor
+                .add_dependencies(strings(&["react"]), scope, false, vec![])
+                .unwrap();
+            assert_eq!(executor.command_line(), expected);
+        }
+    }
+
+    #[test]
+    fn installs_with_the_frozen_template() {
+        let mut executor = CommandExecutor::new(false, &NPM_PACKAGE_MANAGER);
+        executor.install_dependencies(true, true, vec![]).unwrap();
+        assert_eq!(executor.command_line(), "npm ci '--omit=dev'");
+        let mut executor = CommandExecutor::new(false, &YARN_BERRY_PACKAGE_MANAGER);
+        executor.install_dependencies(true, false, vec![]).unwrap();
+        assert_eq!(executor.command_line(), "yarn install --immutable");
+        let mut executor = CommandExecutor::new(false, &YARN_BERRY_PACKAGE_MANAGER);
+        assert!(executor.install_dependencies(false, true, vec![]).is_err());
+    }
+
+    #[test]
+    fn runs_binaries_with_the_script_executor() {
+        let args = strings(&["nx", "build", "api"]);
+        let mut executor = CommandExecutor::new(true, &PNPM_PACKAGE_MANAGER);
+        executor.run_binary(args.clone()).unwrap();
+        assert_eq!(executor.command_line(), "pnpm nx build api");
+        let mut executor = CommandExecutor::new(false, &PNPM_PACKAGE_MANAGER);
+        executor.run_binary(args).unwrap();
+        assert_eq!(executor.command_line(), "nx build api");
+    }
+
+    #[test]
+    fn detects_yarn_berry() {
+        let directory = tempdir().unwrap();
+        assert!(!is_yarn_berry(directory.path()));
+        let package_json = directory.path().join(PACKAGE_JSON_FILE);
+        write(&package_json, r#"{"packageManager": "yarn@1.22.19"}"#).unwrap();
+        assert!(!is_yarn_berry(directory.path()));
+        write(
+            &package_json,
+            r#"{"packageManager": "yarn@3.2.0+sha224.abc"}"#,
+        )
+        .unwrap();
+        assert!(is_yarn_berry(directory.path()));
+        write(&package_json, r#"{"packageManager": "pnpm@8.0.0"}"#).unwrap();
+        assert!(!is_yarn_berry(directory.path()));
+        write(
+            directory.path().join(YARNRC_FILE),
+            "nodeLinker: node-modules\n",
+        )
+        .unwrap();
+        assert!(is_yarn_berry(directory.path()));
+    }
+}
@@ -0,0 +1,282 @@
+use super::{
+    constants::PACKAGE_JSON_FILE, dependencies::RequirementScope, LibraryError,
+    WriteDependencies,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env::current_dir,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+/// The root `package.json` of the workspace. It is kept as raw JSON (in its original
+/// order) so that every field esteem does not manage is written back untouched.
+#[derive(Debug, Serialize)]
+pub struct PackageJson(Map<String, Value>);
+
+impl PackageJson {
+    pub fn from_current_directory() -> Result<Self, LibraryError> {
+        let path = current_dir().unwrap().join(PACKAGE_JSON_FILE);
+        let data = read_to_string(&path)
+            .map_err(|err| LibraryError(format!("Could not read {path:?}: {err}")))?;
+        serde_json::from_str(&data)
+            .map(Self)
+            .map_err(|err| LibraryError(format!("Could not parse {path:?}: {err}")))
+    }
+
+    /// returns the packages declared under `key` along with their versions
+    pub fn get_dependencies(&self, key: &str) -> BTreeMap<String, String> {
+        self.0
+            .get(key)
+            .and_then(Value::as_object)
+            .map(|deps| {
+                deps.iter()
+                    .filter_map(|(name, version)| {
+                        version.as_str().map(|v| (name.to_owned(), v.to_owned()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// returns the versions of the packages declared in any of the dependency sections,
+    /// the first section (in the order of [`RequirementScope`]) that declares a package
+    /// wins
+    pub fn get_all_versions(&self) -> BTreeMap<String, String> {
+        let mut versions = BTreeMap::new();
+        for scope in RequirementScope::all() {
+            for (package, version) in self.get_dependencies(scope.package_json_key()) {
+                versions.entry(package).or_insert(version);
+            }
+        }
+        versions
+    }
+
+    /// replaces the packages declared under `key`, keeping the position of the key. The
+    /// key is not added if it did not exist and there is nothing to declare.
+    pub fn set_dependencies(
+        &mut self,
+        key: &str,
+        dependencies: BTreeMap<String, String>,
+    ) {
+        if dependencies.is_empty() && !self.0.contains_key(key) {
+            return;
+        }
+        let dependencies = dependencies
+            .into_iter()
+            .map(|(name, version)| (name, Value::String(version)))
+            .collect();
+        self.0.insert(key.to_owned(), Value::Object(dependencies));
+    }
+
+    /// removes the entries of `overrides`, `pnpm.overrides` and `resolutions` that select
+    /// one of the `removed` packages, returning the selectors that were removed
+    pub fn prune_overrides(&mut self, removed: &BTreeSet<String>) -> Vec<String> {
+        let mut pruned = vec![];
+        let mut prune = |overrides: Option<&mut Value>| {
+            if let Some(Value::Object(overrides)) = overrides {
+                overrides.retain(|selector, _| {
+                    let applies_to_removed = selected_package(selector)
+                        .is_some_and(|package| removed.contains(package));
+                    if applies_to_removed {
+                        pruned.push(selector.to_owned());
+                    }
+                    !applies_to_removed
+                });
+            }
+        };
+        prune(self.0.get_mut("overrides"));
+        prune(self.0.get_mut("resolutions"));
+        prune(
+            self.0
+                .get_mut("pnpm")
+                .and_then(|pnpm| pnpm.get_mut("overrides")),
+        );
+        pruned
+    }
+}
+
+impl WriteDependencies for PackageJson {
+    fn get_path(&self) -> PathBuf {
+        current_dir().unwrap().join(PACKAGE_JSON_FILE)
+    }
+}
+
+/// reads the name of the package in `directory` from its `package.json`, if it has one
+pub(crate) fn read_package_name(directory: &Path) -> Option<String> {
+    let data = read_to_string(directory.join(PACKAGE_JSON_FILE)).ok()?;
+    let manifest = serde_json::from_str::<Value>(&data).ok()?;
+    manifest.get("name")?.as_str().map(String::from)
+}
+
+/// returns the outermost package of an override selector, which is the package the
+/// override is scoped to. Selectors look like `foo`, `foo@<2`, `foo>bar` (pnpm),
+/// `foo/bar` or `**/bar` (yarn).
+fn selected_package(selector: &str) -> Option<&str> {
+    let first = selector.split('>').next().unwrap_or(selector);
+    // skip leading globs like `**/`
+    let rest = &first[first.find(|c| c != '*' && c != '/')?..];
+    let end = match rest.starts_with('@') {
+        true => rest
+            .find('/')
+            .and_then(|scope| rest[scope + 1..].find('/').map(|i| scope + 1 + i)),
+        false => rest.find('/'),
+    };
+    let package = &rest[..end.unwrap_or(rest.len())];
+    // strip the version range, a leading `@` belongs to the scope
+    let version = package[1..].find('@').map_or(package.len(), |i| i + 1);
+    Some(&package[..version])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{
+        PACKAGE_JSON_DEPENDENCIES_KEY, PACKAGE_JSON_DEV_DEPENDENCIES_KEY,
+        PACKAGE_JSON_OPTIONAL_DEPENDENCIES_KEY, PACKAGE_JSON_PEER_DEPENDENCIES_KEY,
+    };
+
+    fn parse(data: &str) -> PackageJson {
+        PackageJson(serde_json::from_str(data).unwrap())
+    }
+
+    fn versions(packages: &[(&str, &str)]) -> BTreeMap<String, String> {
+        packages
+            .iter()
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect()
+    }
+
+    fn removed(packages: &[&str]) -> BTreeSet<String> {
+        packages.iter().map(|package| package.to_string()).collect()
+    }
+
+    #[test]
+    fn keeps_unmanaged_fields_in_order() {
+        let mut package_json = parse(
+            r#"{
+  "name": "workspace",
+  "scripts": { "start": "nx serve", "build": "nx build" },
+  "dependencies": { "react": "^18.2.0", "next": "13.0.0" },
+  "private": true,
+  "devDependencies": { "nx": "15.0.0" },
+  "workspaces": ["libs/*"],
+  "engines": { "node": ">=18" }
+}"#,
+        );
+        package_json.set_dependencies(
+            PACKAGE_JSON_DEPENDENCIES_KEY,
+            versions(&[("zod", "3.0.0"), ("react", "^18.2.0")]),
+        );
+        package_json.set_dependencies(PACKAGE_JSON_DEV_DEPENDENCIES_KEY, BTreeMap::new());
+        assert_eq!(
+            serde_json::to_string(&package_json).unwrap(),
+            concat!(
+                r#"{"name":"workspace","scripts":{"start":"nx serve","build":"nx build"},"#,
+                r#""dependencies":{"react":"^18.2.0","zod":"3.0.0"},"private":true,"#,
+                r#""devDependencies":{},"workspaces":["libs/*"],"engines":{"node":">=18"}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn adds_only_the_sections_with_dependencies() {
+        let mut package_json = parse(r#"{"name": "workspace"}"#);
+        package_json
+            .set_dependencies(PACKAGE_JSON_PEER_DEPENDENCIES_KEY, BTreeMap::new());
+        package_json.set_dependencies(
+            PACKAGE_JSON_OPTIONAL_DEPENDENCIES_KEY,
+            versions(&[("fsevents", "2.3.2")]),
+        );
+        assert_eq!(
+            serde_json::to_string(&package_json).unwrap(),
+            r#"{"name":"workspace","optionalDependencies":{"fsevents":"2.3.2"}}"#
+        );
+    }
+
+    #[test]
+    fn reads_versions_in_the_order_of_the_scopes() {
+        let package_json = parse(
+            r#"{
+  "peerDependencies": { "react": ">=17" },
+  "devDependencies": { "react": "18.2.0", "nx": "15.0.0" },
+  "dependencies": { "next": "13.0.0", "local": { "invalid": true } }
+}"#,
+        );
+        assert_eq!(
+            package_json.get_all_versions(),
+            versions(&[("next", "13.0.0"), ("nx", "15.0.0"), ("react", "18.2.0")])
+        );
+    }
+
+    #[test]
+    fn prunes_the_overrides_of_removed_packages() {
+        let mut package_json = parse(
+            r#"{
+  "overrides": {
+    "react": "18.2.0",
+    "@babel/core@<7": { "semver": "6.3.1" },
+    "next": { "postcss": "8.4.31" }
+  },
+  "resolutions": { "**/react": "18.2.0", "next/postcss": "8.4.31", "@babel/core/semver": "6.3.1" },
+  "pnpm": {
+    "overrides": { "react@<18>scheduler": "0.23.0", "postcss": "8.4.31" },
+    "neverBuiltDependencies": ["fsevents"]
+  }
+}"#,
+        );
+        let pruned = package_json.prune_overrides(&removed(&["react", "@babel/core"]));
+        assert_eq!(
+            pruned,
+            [
+                "react",
+                "@babel/core@<7",
+                "**/react",
+                "@babel/core/semver",
+                "react@<18>scheduler"
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&package_json).unwrap(),
+            concat!(
+                r#"{"overrides":{"next":{"postcss":"8.4.31"}},"#,
+                r#""resolutions":{"next/postcss":"8.4.31"},"#,
+                r#""pnpm":{"overrides":{"postcss":"8.4.31"},"neverBuiltDependencies":["fsevents"]}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn prunes_nothing_without_overrides() {
+        let mut package_json = parse(r#"{"name": "workspace", "pnpm": {}}"#);
+        assert!(package_json
+            .prune_overrides(&removed(&["react"]))
+            .is_empty());
+        assert_eq!(
+            serde_json::to_string(&package_json).unwrap(),
+            r#"{"name":"workspace","pnpm":{}}"#
+        );
+    }
+
+    #[test]
+    fn reads_the_package_of_selectors() {
+        for (selector, package) in [
+            ("react", Some("react")),
+            ("react@<18", Some("react")),
+            ("@babel/core", Some("@babel/core")),
+            ("@babel/core@7", Some("@babel/core")),
+            ("react>scheduler", Some("react")),
+            ("@babel/core@7>semver", Some("@babel/core")),
+            ("next/postcss", Some("next")),
+            ("@scope/a/b", Some("@scope/a")),
+            ("**/react", Some("react")),
+            ("**/@babel/core", Some("@babel/core")),
+            ("**", None),
+            ("", None),
+        ] {
+            assert_eq!(selected_package(selector), package, "{selector:?}");
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use super::constants::{LOCKFILES, NODE_MODULES_DIRECTORY, PACKAGE_JSON_FILE};
+use super::LibraryError;
+use duct::cmd;
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env::current_dir,
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+
+/// returns the binaries that the installed `packages` provide, mapped to the package that
+/// provides them. Packages that are not installed are skipped.
+pub(crate) fn get_binaries<'a>(
+    packages: impl IntoIterator<Item = &'a String>,
+) -> BTreeMap<String, String> {
+    let mut ret = BTreeMap::new();
+    for package in packages {
+        let path = Path::new(NODE_MODULES_DIRECTORY)
+            .join(package)
+            .join(PACKAGE_JSON_FILE);
+        let manifest = read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<Value>(&data).ok());
+        match manifest.as_ref().and_then(|m| m.get("bin")) {
+            // a single binary is named after the package, without its scope
+            Some(Value::String(_)) => {
+                let name = package.rsplit('/').next().unwrap_or(package);
+                ret.insert(name.to_owned(), package.to_owned());
+            }
+            Some(Value::Object(binaries)) => {
+                for name in binaries.keys() {
+                    ret.insert(name.to_owned(), package.to_owned());
+                }
+            }
+            _ => {}
+        }
+    }
+    ret
+}
+
+/// The scope of the packages that provide the types of packages without bundled ones
+const TYPES_SCOPE: &str = "@types";
+
+/// The keys of a manifest that point to the bundled types of a package
+const TYPES_KEYS: [&str; 2] = ["types", "typings"];
+
+/// returns the `@types` package that provides the types of `package`, eg: `@types/node`
+/// for `node` and `@types/babel__core` for `@babel/core`. Packages of the `@types` scope
+/// do not have one.
+pub(crate) fn get_types_package(package: &str) -> Option<String> {
+    if package.starts_with(&format!("{TYPES_SCOPE}/")) {
+        return None;
+    }
+    match package.strip_prefix('@') {
+        Some(scoped) => Some(format!("{TYPES_SCOPE}/{}", scoped.replacen('/', "__", 1))),
+        None => Some(format!("{TYPES_SCOPE}/{package}")),
+    }
+}
+
+/// returns whether the installed `package` bundles its own types, either declared by its
+/// manifest (`types`, `typings` or a `types` condition of `exports`) or next to its entry
+/// point. Returns `None` if the package is not installed.
+pub(crate) fn has_bundled_types(package: &str) -> Option<bool> {
+    let directory = Path::new(NODE_MODULES_DIRECTORY).join(package);
+    let manifest = read_to_string(directory.join(PACKAGE_JSON_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_str::<Value>(&data).ok())?;
+    if TYPES_KEYS.iter().any(|key| manifest.get(key).is_some())
+        || manifest.get("exports").is_some_and(has_types_condition)
+    {
+        return Some(true);
+    }
+    let main = manifest
+        .get("main")
+        .and_then(Value::as_str)
+        .unwrap_or("index.js");
+    let main = main.strip_prefix("./").unwrap_or(main);
+    let declarations = match main.rsplit_once('.') {
+        Some((stem, "js" | "cjs" | "mjs")) => format!("{stem}.d.ts"),
+        _ => format!("{}.d.ts", main.trim_end_matches('/')),
+    };
+    Some(directory.join(declarations).exists() || directory.join("index.d.ts").exists())
+}
+
+fn has_types_condition(exports: &Value) -> bool {
+    match exports {
+        Value::Object(conditions) => conditions
+            .iter()
+            .any(|(key, value)| key == "types" || has_types_condition(value)),
+        Value::Array(exports) => exports.iter().any(has_types_condition),
+        _ => false,
+    }
+}
+
+/// whether `package` is installed or present in one of the lockfiles of the workspace
+pub(crate) fn is_known(package: &str) -> bool {
+    if Path::new(NODE_MODULES_DIRECTORY).join(package).exists() {
+        return true;
+    }
+    // `node_modules/x"` in `package-lock.json`, `/x/1.0.0` or `x@1.0.0` in
+    // `pnpm-lock.yaml` and `x@^1.0.0` in `yarn.lock`
+    let patterns = [
+        format!("{NODE_MODULES_DIRECTORY}/{package}\""),
+        format!("/{package}/"),
+        format!("{package}@"),
+    ];
+    LOCKFILES
+        .iter()
+        .filter_map(|lockfile| read_to_string(lockfile).ok())
+        .any(|data| patterns.iter().any(|p| data.contains(p.as_str())))
+}
+
+/// whether `package` is published to the registry, according to `npm view`. Returns
+/// `None` if the registry could not be asked, eg: when `npm` is not installed.
+pub(crate) fn is_published(package: &str) -> Option<bool> {
+    let output = cmd!("npm", "view", package, "name")
+        .stdout_capture()
+        .stderr_capture()
+        .unchecked()
+        .run()
+        .ok()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match output.status.success() {
+        true => Some(!output.stdout.is_empty()),
+        false if stderr.contains("E404") => Some(false),
+        false => {
+            debug!("Could not ask the registry about {package:?}: {stderr}");
+            None
+        }
+    }
+}
+
+/// The files that package managers write to `node_modules` after every install
+const INSTALL_MARKERS: [&str; 4] = [
+    ".modules.yaml",
+    ".package-lock.json",
+    ".yarn-integrity",
+    ".yarn-state.yml",
+];
+
+/// whether one of the lockfiles was modified after the packages were last installed, in
+/// which case `node_modules` might not match it
+pub(crate) fn is_outdated() -> bool {
+    let modified = |path: &Path| path.metadata().and_then(|m| m.modified()).ok();
+    let node_modules = Path::new(NODE_MODULES_DIRECTORY);
+    let installed = INSTALL_MARKERS
+        .iter()
+        .filter_map(|marker| modified(&node_modules.join(marker)))
+        .max()
+        .or_else(|| modified(node_modules));
+    let locked = LOCKFILES
+        .iter()
+        .filter_map(|lockfile| modified(Path::new(lockfile)))
+        .max();
+    matches!((installed, locked), (Some(installed), Some(locked)) if locked > installed)
+}
+
+/// The sections of a manifest whose packages are installed along with the package
+const INSTALLED_KEYS: [&str; 3] =
+    ["dependencies", "optionalDependencies", "peerDependencies"];
+
+/// A package installed in `node_modules`
+#[derive(Debug)]
+pub(crate) struct InstalledPackage {
+    pub(crate) name: String,
+    pub(crate) version: Option<String>,
+    /// the SPDX expression of its license, eg: `MIT` or `(MIT OR Apache-2.0)`
+    pub(crate) license: Option<String>,
+    /// the size of the files of the package, without the packages nested in it
+    pub(crate) size: u64,
+    /// the directories of the packages it depends on
+    pub(crate) dependencies: Vec<PathBuf>,
+}
+
+/// The packages installed in the `node_modules` of the workspace, read lazily. Packages
+/// are identified by their directory with symbolic links resolved, so that a package
+/// linked from several places (eg: by pnpm) is only counted once.
+#[derive(Debug)]
+pub(crate) struct Installation {
+    /// the root of the workspace, with symbolic links resolved
+    root: PathBuf,
+    packages: BTreeMap<PathBuf, InstalledPackage>,
+}
+
+impl Installation {
+    pub(crate) fn from_current_directory() -> Result<Self, LibraryError> {
+        let root = current_dir().unwrap();
+        match root.join(NODE_MODULES_DIRECTORY).is_dir() {
+            true => Ok(Self {
+                root: root.canonicalize().unwrap_or(root),
+                packages: BTreeMap::new(),
+            }),
+            false => Err(LibraryError(format!(
+                "{NODE_MODULES_DIRECTORY:?} does not exist, please install the dependencies of the workspace first"
+            ))),
+        }
+    }
+
+    /// returns the directories of `packages` and all the packages they depend on.
+    /// Packages that are not installed are skipped.
+    pub(crate) fn get_closure<'a>(
+        &mut self,
+        packages: impl IntoIterator<Item = &'a String>,
+    ) -> BTreeSet<PathBuf> {
+        let mut ret = BTreeSet::new();
+        let mut to_visit = vec![];
+        for package in packages {
+            match self.resolve(package) {
+                Some(directory) => to_visit.push(directory),
+                None => {
+                    debug!("{package:?} is not installed in {NODE_MODULES_DIRECTORY:?}")
+                }
+            }
+        }
+        while let Some(directory) = to_visit.pop() {
+            if ret.contains(&directory) {
+                continue;
+            }
+            let package = self
+                .packages
+                .entry(directory.clone())
+                .or_insert_with(|| read_installed_package(&self.root, &directory));
+            to_visit.extend(package.dependencies.iter().cloned());
+            ret.insert(directory);
+        }
+        ret
+    }
+
+    /// returns the directory that `package` is installed at in the root of the workspace
+    pub(crate) fn resolve(&self, package: &str) -> Option<PathBuf> {
+        resolve_package(&self.root, &self.root, package)
+    }
+
+    /// returns the package installed in `directory`, which must be part of a closure
+    /// returned by [`Installation::get_closure`]
+    pub(crate) fn get(&self, directory: &Path) -> &InstalledPackage {
+        &self.packages[directory]
+    }
+}
+
+/// returns the directory of `package` as node resolves it from `directory`: in the
+/// `node_modules` of `directory` or of one of its ancestors, up to the root of the
+/// workspace
+fn resolve_package(workspace: &Path, directory: &Path, package: &str) -> Option<PathBuf> {
+    directory
+        .ancestors()
+        .take_while(|a| a.starts_with(workspace))
+        .filter(|a| a.file_name() != Some(NODE_MODULES_DIRECTORY.as_ref()))
+        .map(|a| a.join(NODE_MODULES_DIRECTORY).join(package))
+        .find(|candidate| candidate.is_dir())
+        .and_then(|candidate| candidate.canonicalize().ok())
+}
+
+fn read_installed_package(workspace: &Path, directory: &Path) -> InstalledPackage {
+    let manifest = read_to_string(directory.join(PACKAGE_JSON_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_str::<Value>(&data).ok())
+        .unwrap_or_default();
+    let name = manifest
+        .get("name")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .unwrap_or_else(|| directory.file_name().unwrap().to_string_lossy().to_string());
+    let dependencies = INSTALLED_KEYS
+        .iter()
+        .filter_map(|key| manifest.get(key).and_then(Value::as_object))
+        .flat_map(|packages| packages.keys())
+        // optional and peer dependencies might not be installed
+        .filter_map(|package| resolve_package(workspace, directory, package))
+        .collect();
+    InstalledPackage {
+        name,
+        version: manifest
+            .get("version")
+            .and_then(Value::as_str)
+            .map(String::from),
+        license: get_license(&manifest),
+        size: get_directory_size(directory),
+        dependencies,
+    }
+}
+
+/// returns the license of a manifest, which is either an SPDX expression, an object with
+/// a `type` or (in older packages) a list of such objects
+fn get_license(manifest: &Value) -> Option<String> {
+    let get_type = |license: &Value| match license {
+        Value::String(license) => Some(license.to_owned()),
+        Value::Object(license) => license
+            .get("type")
+            .and_then(Value::as_str)
+            .map(String::from),
+        _ => None,
+    };
+    if let Some(license) = manifest.get("license").and_then(get_type) {
+        return Some(license);
+    }
+    let licenses = manifest
+        .get("licenses")
+        .and_then(Value::as_array)?
+        .iter()
+        .filter_map(get_type)
+        .collect::<Vec<_>>();
+    match licenses.len() {
+        0 => None,
+        1 => licenses.into_iter().next(),
+        _ => Some(format!("({})", licenses.join(" OR "))),
+    }
+}
+
+/// returns the size of the files in `directory`, without following symbolic links or
+/// entering nested `node_modules`
+fn get_directory_size(directory: &Path) -> u64 {
+    let entries = match read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            match metadata.is_dir() {
+                true if entry.file_name() == NODE_MODULES_DIRECTORY => None,
+                true => Some(get_directory_size(&entry.path())),
+                false => Some(metadata.len()),
+            }
+        })
+        .sum()
+}
@@ -0,0 +1,139 @@
+use super::{dependencies::RequirementScope, workspace::EsteemWorkspace};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// The most projects that may use a workspace dependency for it to be moved into them
+pub const DEFAULT_MAX_USERS: usize = 3;
+
+/// The least share of projects that must declare a dependency for it to be moved into the
+/// workspace
+pub const DEFAULT_MIN_SHARE: f64 = 0.9;
+
+/// A change of the place where a dependency is declared
+#[derive(Debug, Clone, PartialEq)]
+pub enum Suggestion {
+    /// a dependency of the workspace that is only used by a few projects, and should be
+    /// declared by them instead
+    MoveToProjects {
+        package: String,
+        scope: RequirementScope,
+        projects: BTreeSet<String>,
+    },
+    /// a dependency that nearly every project declares, and should be declared by the
+    /// workspace instead. `projects` are the ones declaring it in `scope`, which are the
+    /// only ones it is moved out of.
+    MoveToWorkspace {
+        package: String,
+        scope: RequirementScope,
+        projects: BTreeSet<String>,
+    },
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MoveToProjects {
+                package,
+                scope,
+                projects,
+            } => write!(
+                f,
+                "move the {scope} package {package:?} from the workspace to {}, the only projects using it",
+                join(projects)
+            ),
+            Self::MoveToWorkspace {
+                package,
+                scope,
+                projects,
+            } => write!(
+                f,
+                "move the {scope} package {package:?} to the workspace, it is declared by {} projects: {}",
+                projects.len(),
+                join(projects)
+            ),
+        }
+    }
+}
+
+fn join(projects: &BTreeSet<String>) -> String {
+    projects
+        .iter()
+        .map(|p| format!("{p:?}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// returns the suggestions for the dependencies of the workspace. `usage` maps every
+/// project to the packages it uses, a workspace dependency used by at least one and at
+/// most `max_users` projects is moved into them. A dependency declared by at least
+/// `min_share` of all projects is moved into the workspace.
+pub fn get_suggestions(
+    workspace: &EsteemWorkspace,
+    usage: &BTreeMap<String, BTreeSet<String>>,
+    max_users: usize,
+    min_share: f64,
+) -> Vec<Suggestion> {
+    let mut ret = vec![];
+    for scope in RequirementScope::all() {
+        for package in workspace.dependencies.get_scope(scope) {
+            let projects = usage
+                .iter()
+                .filter(|(_, packages)| packages.contains(package))
+                .map(|(project, _)| project.to_owned())
+                .collect::<BTreeSet<_>>();
+            if !projects.is_empty() && projects.len() <= max_users {
+                ret.push(Suggestion::MoveToProjects {
+                    package: package.to_owned(),
+                    scope,
+                    projects,
+                });
+            }
+        }
+    }
+    // the packages of dependency groups count as declared by the projects including them
+    let mut declared =
+        BTreeMap::<String, BTreeMap<RequirementScope, BTreeSet<String>>>::new();
+    for project in &workspace.all_projects_rep {
+        let dependencies = workspace.resolve(&project.dependencies);
+        for scope in RequirementScope::all() {
+            for package in dependencies.get_scope(scope) {
+                declared
+                    .entry(package.to_owned())
+                    .or_default()
+                    .entry(scope)
+                    .or_default()
+                    .insert(project.name.to_owned());
+            }
+        }
+    }
+    let workspace_dependencies = workspace
+        .resolve(&workspace.dependencies)
+        .get_all_dependencies();
+    let total = workspace.all_projects_rep.len();
+    for (package, mut scopes) in declared {
+        let users = scopes.values().flatten().collect::<BTreeSet<_>>().len();
+        let is_shared = total > 1 && users as f64 >= min_share * total as f64;
+        if !is_shared || workspace_dependencies.contains(&package) {
+            continue;
+        }
+        // a package is promoted to the scope that most projects declare it in, ties are
+        // broken by the order of the scopes. Peer and optional dependencies are part of
+        // what a project publishes, so they are never moved.
+        let scope = [RequirementScope::Development, RequirementScope::Required]
+            .into_iter()
+            .max_by_key(|scope| scopes.get(scope).map_or(0, BTreeSet::len));
+        let projects = scope
+            .and_then(|scope| scopes.remove(&scope))
+            .unwrap_or_default();
+        if let (Some(scope), false) = (scope, projects.is_empty()) {
+            ret.push(Suggestion::MoveToWorkspace {
+                package,
+                scope,
+                projects,
+            });
+        }
+    }
+    ret
+}
@@ -1,7 +1,11 @@
 use super::{
-    constants::PROJECT_FILE, dependencies::EsteemDependencies,
-    AddEsteemDevelopmentDependency, AddEsteemRequiredDependency, LibraryError,
-    RemoveEsteemDevelopmentDependency, RemoveEsteemRequiredDependency, WriteDependencies,
+    constants::{PROJECT_FILE, TARGETS_KEY},
+    dependencies::EsteemDependencies,
+    AddEsteemDevelopmentDependency, AddEsteemOptionalDependency, AddEsteemPeerDependency,
+    AddEsteemRequiredDependency, AddEsteemTargetDependency, LibraryError,
+    RemoveEsteemDevelopmentDependency, RemoveEsteemOptionalDependency,
+    RemoveEsteemPeerDependency, RemoveEsteemRequiredDependency,
+    RemoveEsteemTargetDependency, WriteDependencies,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -51,6 +55,55 @@ impl EsteemProject {
             }
         }
     }
+
+    /// the tags of this project as declared in its description file
+    pub fn get_tags(&self) -> Vec<String> {
+        self.other
+            .get("tags")
+            .and_then(Value::as_array)
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// the type of this project as declared in its description file, eg: `application`
+    pub fn get_project_type(&self) -> Option<&str> {
+        self.other.get("projectType").and_then(Value::as_str)
+    }
+
+    /// the output path of the `build` target of this project, relative to the root of
+    /// the workspace
+    pub fn get_output_path(&self) -> Option<PathBuf> {
+        self.other
+            .get(TARGETS_KEY)
+            .and_then(|targets| targets.get("build"))
+            .and_then(|build| build.get("options"))
+            .and_then(|options| options.get("outputPath"))
+            .and_then(Value::as_str)
+            .map(PathBuf::from)
+    }
+
+    /// the directory of the sources of this project, relative to the root of the workspace
+    pub(crate) fn get_source_root(&self) -> Option<PathBuf> {
+        self.other
+            .get("sourceRoot")
+            .and_then(Value::as_str)
+            .map(PathBuf::from)
+    }
+
+    /// the targets of this project as declared in its description file, keyed by their
+    /// name
+    pub(crate) fn get_targets(&self) -> BTreeMap<&str, &Value> {
+        self.other
+            .get(TARGETS_KEY)
+            .and_then(Value::as_object)
+            .map(|targets| targets.iter().map(|(k, v)| (k.as_str(), v)).collect())
+            .unwrap_or_default()
+    }
 }
 
 impl AddEsteemRequiredDependency for EsteemProject {
@@ -65,6 +118,18 @@ impl AddEsteemDevelopmentDependency for EsteemProject {
     }
 }
 
+impl AddEsteemPeerDependency for EsteemProject {
+    fn add_peer_dependency(&mut self, dependency: String) {
+        self.dependencies.add_peer_dependency(dependency);
+    }
+}
+
+impl AddEsteemOptionalDependency for EsteemProject {
+    fn add_optional_dependency(&mut self, dependency: String) {
+        self.dependencies.add_optional_dependency(dependency);
+    }
+}
+
 impl WriteDependencies for EsteemProject {
     fn get_path(&self) -> PathBuf {
         self.description_file_path.clone()
@@ -88,3 +153,33 @@ impl RemoveEsteemDevelopmentDependency for EsteemProject {
         self.dependencies.remove_development_dependency(dependency)
     }
 }
+
+impl RemoveEsteemPeerDependency for EsteemProject {
+    fn remove_peer_dependency(&mut self, dependency: String) -> Result<(), LibraryError> {
+        self.dependencies.remove_peer_dependency(dependency)
+    }
+}
+
+impl RemoveEsteemOptionalDependency for EsteemProject {
+    fn remove_optional_dependency(
+        &mut self,
+        dependency: String,
+    ) -> Result<(), LibraryError> {
+        self.dependencies.remove_optional_dependency(dependency)
+    }
+}
+
+impl AddEsteemTargetDependency for EsteemProject {
+    fn add_target_dependency(&mut self, target: String, dependency: String) {
+        self.dependencies.add_target_dependency(target, dependency);
+    }
+}
+
+impl RemoveEsteemTargetDependency for EsteemProject {
+    fn remove_target_dependency(
+        &mut self,
+        dependency: String,
+    ) -> Result<(), LibraryError> {
+        self.dependencies.remove_target_dependency(dependency)
+    }
+}
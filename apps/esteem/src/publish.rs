@@ -0,0 +1,113 @@
+use super::{
+    config::EsteemConfig, dependencies::RequirementScope, graph::NxProject,
+    manifest::PackageJson, workspace::EsteemWorkspace, LibraryError, WriteDependencies,
+};
+use serde::Serialize;
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// The placeholder in `publish.nameTemplate` that is replaced by the name of the project
+const PROJECT_PLACEHOLDER: &str = "{project}";
+
+/// The `package.json` of a buildable or publishable project, written to its output
+/// directory
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishablePackageJson {
+    #[serde(skip)]
+    path: PathBuf,
+    name: String,
+    version: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    dependencies: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    peer_dependencies: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    optional_dependencies: BTreeMap<String, String>,
+}
+
+impl PublishablePackageJson {
+    /// builds the manifest of `project_name` from the packages it declares, with the
+    /// versions of the root `package.json`, and the projects it directly depends on
+    pub fn new(
+        project_name: &str,
+        path: PathBuf,
+        workspace: &EsteemWorkspace,
+        graph: &NxProject,
+        root_package_json: &PackageJson,
+        use_caret: bool,
+    ) -> Result<Self, LibraryError> {
+        let project = workspace.get_project(project_name.to_owned())?;
+        let root_versions = root_package_json.get_all_versions();
+        let internal_packages = workspace.get_internal_packages();
+        let dependencies = workspace.resolve(&project.dependencies);
+        let versions = |scope: RequirementScope| {
+            dependencies
+                .get_scope(scope)
+                .iter()
+                .map(|package| {
+                    // internal packages are referenced with `workspace:` in the root
+                    // `package.json`, they are published with the version of their project
+                    if let Some(internal) = internal_packages.get(package) {
+                        let (_, version) = get_published_name_and_version(&internal.project);
+                        return Ok((package.to_owned(), to_range(&version, use_caret)));
+                    }
+                    let version = root_versions.get(package).ok_or_else(|| {
+                        LibraryError(format!(
+                            "{package:?} is declared by {project_name:?} but does not exist in the root package.json"
+                        ))
+                    })?;
+                    Ok((package.to_owned(), to_range(version, use_caret)))
+                })
+                .collect::<Result<BTreeMap<_, _>, LibraryError>>()
+        };
+        let mut dependencies = versions(RequirementScope::Required)?;
+        for upstream in graph.get_direct_dependencies(project_name) {
+            let (name, version) = get_published_name_and_version(&upstream);
+            dependencies.insert(name, to_range(&version, use_caret));
+        }
+        let (name, version) = get_published_name_and_version(project_name);
+        Ok(Self {
+            path,
+            name,
+            version,
+            dependencies,
+            peer_dependencies: versions(RequirementScope::Peer)?,
+            optional_dependencies: versions(RequirementScope::Optional)?,
+        })
+    }
+}
+
+impl WriteDependencies for PublishablePackageJson {
+    fn get_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+}
+
+/// the name and version a project is published with, from the `publish` configuration
+fn get_published_name_and_version(project_name: &str) -> (String, String) {
+    let config = &EsteemConfig::get().publish;
+    let overrides = config.projects.get(project_name);
+    let name = overrides.and_then(|o| o.name.clone()).unwrap_or_else(|| {
+        config
+            .name_template
+            .replace(PROJECT_PLACEHOLDER, project_name)
+    });
+    let version = overrides
+        .and_then(|o| o.version.clone())
+        .unwrap_or_else(|| config.version.clone());
+    (name, version)
+}
+
+/// turns an exact version like `1.2.3` into a caret range if `use_caret` is set, any
+/// other version is returned as is
+fn to_range(version: &str, use_caret: bool) -> String {
+    let release = version.split(['-', '+']).next().unwrap_or_default();
+    let is_exact = release.split('.').count() == 3
+        && release
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+    match use_caret && is_exact {
+        true => format!("^{version}"),
+        false => version.to_owned(),
+    }
+}
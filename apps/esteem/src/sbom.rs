@@ -0,0 +1,578 @@
+use super::{lockfile::Lockfile, node_modules::Installation, LibraryError};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The value of SPDX fields whose value is not known
+const NO_ASSERTION: &str = "NOASSERTION";
+
+/// The identifier of the project in the SPDX document
+const SPDX_PROJECT_ID: &str = "SPDXRef-Project";
+
+/// The formats that a software bill of materials can be written in
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SbomFormat {
+    CycloneDx,
+    Spdx,
+}
+
+impl SbomFormat {
+    pub const VARIANTS: [&'static str; 2] = ["cyclonedx", "spdx"];
+}
+
+impl FromStr for SbomFormat {
+    type Err = LibraryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cyclonedx" => Ok(Self::CycloneDx),
+            "spdx" => Ok(Self::Spdx),
+            _ => Err(LibraryError(format!("Unknown SBOM format: {s:?}"))),
+        }
+    }
+}
+
+/// A package of the bill of materials, all the installed copies of a version of a
+/// package are the same component
+#[derive(Debug)]
+struct Component {
+    name: String,
+    version: Option<String>,
+    license: Option<String>,
+    /// the Subresource Integrity of its tarball as read from the lockfile
+    integrity: Option<String>,
+    resolved: Option<String>,
+    /// the references of the components it depends on
+    dependencies: BTreeSet<String>,
+}
+
+impl Component {
+    /// the package URL of the component, eg: `pkg:npm/%40babel/core@7.20.0`
+    fn get_purl(&self) -> String {
+        let name = self.name.replacen('@', "%40", 1);
+        match &self.version {
+            Some(version) => format!("pkg:npm/{name}@{version}"),
+            None => format!("pkg:npm/{name}"),
+        }
+    }
+
+    /// returns the hashes of its integrity as pairs of algorithm (eg: `sha512`) and
+    /// hexadecimal digest
+    fn get_hashes(&self) -> Vec<(&str, String)> {
+        self.integrity
+            .iter()
+            .flat_map(|integrity| integrity.split_whitespace())
+            .filter_map(|hash| {
+                let (algorithm, digest) = hash.split_once('-')?;
+                let digest = base64::decode(digest).ok()?;
+                Some((
+                    algorithm,
+                    digest.iter().map(|byte| format!("{byte:02x}")).collect(),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// The software bill of materials of a project: every package installed for it in
+/// isolation
+#[derive(Debug)]
+pub struct Sbom {
+    project_name: String,
+    /// the components by their reference, which is their package URL unless they do not
+    /// have a version
+    components: BTreeMap<String, Component>,
+    /// the references of the packages that the project depends on directly
+    direct: BTreeSet<String>,
+}
+
+impl Sbom {
+    /// creates the bill of materials of `project_name` from the packages it depends on
+    /// directly and all the packages installed for it (`closure`)
+    pub(crate) fn new(
+        project_name: &str,
+        installation: &Installation,
+        lockfile: &Lockfile,
+        direct: &BTreeSet<PathBuf>,
+        closure: &BTreeSet<PathBuf>,
+    ) -> Self {
+        let mut references = BTreeMap::new();
+        let mut components = BTreeMap::<String, Component>::new();
+        for directory in closure {
+            let installed = installation.get(directory);
+            let locked = installed
+                .version
+                .as_ref()
+                .and_then(|version| lockfile.get(&installed.name, version));
+            let component = Component {
+                name: installed.name.to_owned(),
+                version: installed.version.to_owned(),
+                license: installed.license.to_owned(),
+                integrity: locked.and_then(|l| l.integrity.to_owned()),
+                resolved: locked.and_then(|l| l.resolved.to_owned()),
+                dependencies: BTreeSet::new(),
+            };
+            let purl = component.get_purl();
+            if component.integrity.is_none() {
+                debug!("The lockfile does not have the integrity of {purl:?}");
+            }
+            // copies without a version can not be told apart, so each one is its own
+            // component
+            let reference = match component.version {
+                Some(_) => purl,
+                None => format!("{purl}#{}", components.len()),
+            };
+            references.insert(directory, reference.clone());
+            components.entry(reference).or_insert(component);
+        }
+        for directory in closure {
+            let dependencies = installation
+                .get(directory)
+                .dependencies
+                .iter()
+                .filter_map(|dependency| references.get(dependency).cloned());
+            components
+                .get_mut(&references[directory])
+                .unwrap()
+                .dependencies
+                .extend(dependencies);
+        }
+        Self {
+            project_name: project_name.to_owned(),
+            components,
+            direct: direct
+                .iter()
+                .filter_map(|directory| references.get(directory).cloned())
+                .collect(),
+        }
+    }
+
+    pub fn render(&self, format: SbomFormat) -> String {
+        let document = match format {
+            SbomFormat::CycloneDx => serde_json::to_string_pretty(&self.to_cyclonedx()),
+            SbomFormat::Spdx => serde_json::to_string_pretty(&self.to_spdx()),
+        };
+        document.unwrap() + "\n"
+    }
+
+    fn to_cyclonedx(&self) -> CycloneDxDocument {
+        let project_ref = self.project_name.to_owned();
+        let components = self
+            .components
+            .iter()
+            .map(|(reference, component)| {
+                let (group, name) = match component.name.split_once('/') {
+                    Some((group, name)) => (Some(group.to_owned()), name.to_owned()),
+                    None => (None, component.name.to_owned()),
+                };
+                CycloneDxComponent {
+                    component_type: "library",
+                    bom_ref: reference.to_owned(),
+                    group,
+                    name,
+                    version: component.version.to_owned(),
+                    purl: component.get_purl(),
+                    licenses: component
+                        .license
+                        .iter()
+                        .map(|license| match is_spdx_expression(license) {
+                            true => CycloneDxLicense::Expression {
+                                expression: license.to_owned(),
+                            },
+                            false => CycloneDxLicense::License {
+                                license: CycloneDxLicenseName {
+                                    name: license.to_owned(),
+                                },
+                            },
+                        })
+                        .collect(),
+                    hashes: component
+                        .get_hashes()
+                        .into_iter()
+                        .map(|(algorithm, content)| CycloneDxHash {
+                            alg: algorithm.to_uppercase().replacen("SHA", "SHA-", 1),
+                            content,
+                        })
+                        .collect(),
+                    external_references: component
+                        .resolved
+                        .iter()
+                        .map(|url| CycloneDxReference {
+                            reference_type: "distribution",
+                            url: url.to_owned(),
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+        let dependencies = [(project_ref.clone(), &self.direct)]
+            .into_iter()
+            .chain(self.components.iter().map(|(reference, component)| {
+                (reference.to_owned(), &component.dependencies)
+            }))
+            .map(|(reference, depends_on)| CycloneDxDependency {
+                reference,
+                depends_on: depends_on.iter().cloned().collect(),
+            })
+            .collect();
+        CycloneDxDocument {
+            bom_format: "CycloneDX",
+            spec_version: "1.5",
+            version: 1,
+            metadata: CycloneDxMetadata {
+                timestamp: get_timestamp(),
+                tools: vec![CycloneDxTool {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                }],
+                component: CycloneDxProject {
+                    component_type: "application",
+                    bom_ref: project_ref,
+                    name: self.project_name.to_owned(),
+                },
+            },
+            components,
+            dependencies,
+        }
+    }
+
+    fn to_spdx(&self) -> SpdxDocument {
+        // different names can be the same once sanitized (eg: `@a/b-c` and `@a-b/c`), so
+        // the repeated identifiers are numbered
+        let mut used = BTreeSet::new();
+        let ids = self
+            .components
+            .iter()
+            .map(|(reference, component)| {
+                let id = format!(
+                    "SPDXRef-Package-npm-{}-{}",
+                    component.name,
+                    component.version.as_deref().unwrap_or_default()
+                )
+                .replace(|c: char| !c.is_ascii_alphanumeric() && c != '.', "-");
+                let id = (1..)
+                    .map(|count| match count {
+                        1 => id.clone(),
+                        count => format!("{id}-{count}"),
+                    })
+                    .find(|id| !used.contains(id))
+                    .unwrap();
+                used.insert(id.clone());
+                (reference, id)
+            })
+            .collect::<BTreeMap<_, _>>();
+        let project = SpdxPackage {
+            spdx_id: SPDX_PROJECT_ID.to_owned(),
+            name: self.project_name.to_owned(),
+            version_info: None,
+            download_location: NO_ASSERTION.to_owned(),
+            files_analyzed: false,
+            license_concluded: NO_ASSERTION.to_owned(),
+            license_declared: NO_ASSERTION.to_owned(),
+            checksums: vec![],
+            external_refs: vec![],
+        };
+        let packages = self
+            .components
+            .iter()
+            .map(|(reference, component)| SpdxPackage {
+                spdx_id: ids[reference].to_owned(),
+                name: component.name.to_owned(),
+                version_info: component.version.to_owned(),
+                download_location: component
+                    .resolved
+                    .to_owned()
+                    .unwrap_or_else(|| NO_ASSERTION.to_owned()),
+                files_analyzed: false,
+                license_concluded: NO_ASSERTION.to_owned(),
+                license_declared: component
+                    .license
+                    .to_owned()
+                    .filter(|license| is_spdx_expression(license))
+                    .unwrap_or_else(|| NO_ASSERTION.to_owned()),
+                checksums: component
+                    .get_hashes()
+                    .into_iter()
+                    .map(|(algorithm, checksum_value)| SpdxChecksum {
+                        algorithm: algorithm.to_uppercase(),
+                        checksum_value,
+                    })
+                    .collect(),
+                external_refs: vec![SpdxExternalRef {
+                    reference_category: "PACKAGE-MANAGER",
+                    reference_type: "purl",
+                    reference_locator: component.get_purl(),
+                }],
+            });
+        let relationship =
+            |element: &str, relationship_type, related: &str| SpdxRelationship {
+                spdx_element_id: element.to_owned(),
+                relationship_type,
+                related_spdx_element: related.to_owned(),
+            };
+        let relationships = [relationship(
+            "SPDXRef-DOCUMENT",
+            "DESCRIBES",
+            SPDX_PROJECT_ID,
+        )]
+        .into_iter()
+        .chain(self.direct.iter().map(|reference| {
+            relationship(SPDX_PROJECT_ID, "DEPENDS_ON", &ids[reference])
+        }))
+        .chain(self.components.iter().flat_map(|(reference, component)| {
+            component
+                .dependencies
+                .iter()
+                .map(|dependency| {
+                    relationship(&ids[reference], "DEPENDS_ON", &ids[dependency])
+                })
+                .collect::<Vec<_>>()
+        }))
+        .collect();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        SpdxDocument {
+            spdx_version: "SPDX-2.3",
+            data_license: "CC0-1.0",
+            spdx_id: "SPDXRef-DOCUMENT",
+            name: self.project_name.to_owned(),
+            document_namespace: format!(
+                "https://spdx.org/spdxdocs/{}-{}-{nanos}",
+                env!("CARGO_PKG_NAME"),
+                self.project_name
+            ),
+            creation_info: SpdxCreationInfo {
+                created: get_timestamp(),
+                creators: vec![format!(
+                    "Tool: {}-{}",
+                    env!("CARGO_PKG_NAME"),
+                    env!("CARGO_PKG_VERSION")
+                )],
+            },
+            packages: [project].into_iter().chain(packages).collect(),
+            relationships,
+        }
+    }
+}
+
+/// whether a license looks like an SPDX expression (eg: `MIT` or `(MIT OR Apache-2.0)`)
+/// rather than a description (eg: `SEE LICENSE IN LICENSE.md`)
+fn is_spdx_expression(license: &str) -> bool {
+    !license.contains(char::is_whitespace)
+        || [" OR ", " AND ", " WITH "]
+            .iter()
+            .any(|o| license.contains(o))
+}
+
+/// returns the current time in UTC, eg: `2022-07-01T12:00:00Z`
+fn get_timestamp() -> String {
+    humantime::format_rfc3339_seconds(SystemTime::now()).to_string()
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxDocument {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    version: u32,
+    metadata: CycloneDxMetadata,
+    components: Vec<CycloneDxComponent>,
+    dependencies: Vec<CycloneDxDependency>,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxMetadata {
+    timestamp: String,
+    tools: Vec<CycloneDxTool>,
+    component: CycloneDxProject,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxTool {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxProject {
+    #[serde(rename = "type")]
+    component_type: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxComponent {
+    #[serde(rename = "type")]
+    component_type: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<String>,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    purl: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<CycloneDxLicense>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    hashes: Vec<CycloneDxHash>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    external_references: Vec<CycloneDxReference>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum CycloneDxLicense {
+    Expression { expression: String },
+    License { license: CycloneDxLicenseName },
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxLicenseName {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxHash {
+    alg: String,
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CycloneDxReference {
+    #[serde(rename = "type")]
+    reference_type: &'static str,
+    url: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxDependency {
+    #[serde(rename = "ref")]
+    reference: String,
+    depends_on: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxDocument {
+    spdx_version: &'static str,
+    data_license: &'static str,
+    #[serde(rename = "SPDXID")]
+    spdx_id: &'static str,
+    name: String,
+    document_namespace: String,
+    creation_info: SpdxCreationInfo,
+    packages: Vec<SpdxPackage>,
+    relationships: Vec<SpdxRelationship>,
+}
+
+#[derive(Debug, Serialize)]
+struct SpdxCreationInfo {
+    created: String,
+    creators: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxPackage {
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version_info: Option<String>,
+    download_location: String,
+    files_analyzed: bool,
+    license_concluded: String,
+    license_declared: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    checksums: Vec<SpdxChecksum>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    external_refs: Vec<SpdxExternalRef>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxChecksum {
+    algorithm: String,
+    checksum_value: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxExternalRef {
+    reference_category: &'static str,
+    reference_type: &'static str,
+    reference_locator: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxRelationship {
+    spdx_element_id: String,
+    relationship_type: &'static str,
+    related_spdx_element: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(name: &str, version: Option<&str>) -> Component {
+        Component {
+            name: name.to_owned(),
+            version: version.map(String::from),
+            license: None,
+            integrity: None,
+            resolved: None,
+            dependencies: BTreeSet::new(),
+        }
+    }
+
+    #[test]
+    fn numbers_repeated_spdx_identifiers() {
+        let components = [
+            ("pkg:npm/%40a/b-c@1.0.0", component("@a/b-c", Some("1.0.0"))),
+            ("pkg:npm/%40a-b/c@1.0.0", component("@a-b/c", Some("1.0.0"))),
+            ("pkg:npm/lib#0", component("lib", None)),
+            ("pkg:npm/lib#1", component("lib", None)),
+        ];
+        let sbom = Sbom {
+            project_name: "api".to_owned(),
+            components: components
+                .into_iter()
+                .map(|(reference, component)| (reference.to_owned(), component))
+                .collect(),
+            direct: BTreeSet::from(["pkg:npm/lib#1".to_owned()]),
+        };
+        let ids = sbom
+            .to_spdx()
+            .packages
+            .into_iter()
+            .map(|package| package.spdx_id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            [
+                SPDX_PROJECT_ID,
+                "SPDXRef-Package-npm--a-b-c-1.0.0",
+                "SPDXRef-Package-npm--a-b-c-1.0.0-2",
+                "SPDXRef-Package-npm-lib-",
+                "SPDXRef-Package-npm-lib--2",
+            ]
+        );
+        let cyclonedx = sbom.to_cyclonedx();
+        assert_eq!(cyclonedx.components[3].bom_ref, "pkg:npm/lib#1");
+        assert_eq!(cyclonedx.components[3].purl, "pkg:npm/lib");
+    }
+}
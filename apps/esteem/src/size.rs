@@ -0,0 +1,193 @@
+use super::{node_modules::Installation, LibraryError};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// The number of the heaviest packages listed when none is configured
+pub const DEFAULT_TOP: usize = 10;
+
+/// The units that sizes are displayed in, each one 1000 times the previous one
+const UNITS: [&str; 4] = ["B", "kB", "MB", "GB"];
+
+/// The formats that the size report can be written in
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeFormat {
+    Table,
+    Json,
+}
+
+impl SizeFormat {
+    pub const VARIANTS: [&'static str; 2] = ["table", "json"];
+}
+
+impl FromStr for SizeFormat {
+    type Err = LibraryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            _ => Err(LibraryError(format!("Unknown size format: {s:?}"))),
+        }
+    }
+}
+
+/// The disk footprint of the packages installed for the whole workspace and for each
+/// project in isolation
+#[derive(Debug, Serialize)]
+pub struct SizeReport {
+    workspace: Footprint,
+    projects: BTreeMap<String, Footprint>,
+    heaviest: Vec<HeavyPackage>,
+}
+
+#[derive(Debug, Serialize)]
+struct Footprint {
+    packages: usize,
+    /// in bytes
+    size: u64,
+}
+
+/// A package (all of its installed versions) along with the projects that need it
+#[derive(Debug, Serialize)]
+struct HeavyPackage {
+    name: String,
+    /// in bytes
+    size: u64,
+    projects: BTreeSet<String>,
+}
+
+impl SizeReport {
+    /// creates the report from the packages installed for the whole workspace (`full`)
+    /// and the ones needed by each project, listing the `top` heaviest packages
+    pub(crate) fn new(
+        installation: &Installation,
+        full: &BTreeSet<PathBuf>,
+        projects: &BTreeMap<String, BTreeSet<PathBuf>>,
+        top: usize,
+    ) -> Self {
+        let footprint = |packages: &BTreeSet<PathBuf>| Footprint {
+            packages: packages.len(),
+            size: packages.iter().map(|p| installation.get(p).size).sum(),
+        };
+        let mut heaviest = BTreeMap::<&str, HeavyPackage>::new();
+        for directory in full {
+            let installed = installation.get(directory);
+            let name = installed.name.as_str();
+            let package = heaviest.entry(name).or_insert_with(|| HeavyPackage {
+                name: name.to_owned(),
+                size: 0,
+                projects: BTreeSet::new(),
+            });
+            package.size += installed.size;
+            package.projects.extend(
+                projects
+                    .iter()
+                    .filter(|(_, packages)| packages.contains(directory))
+                    .map(|(project, _)| project.to_owned()),
+            );
+        }
+        let mut heaviest = heaviest.into_values().collect::<Vec<_>>();
+        heaviest.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        heaviest.truncate(top);
+        Self {
+            workspace: footprint(full),
+            projects: projects
+                .iter()
+                .map(|(project, packages)| (project.to_owned(), footprint(packages)))
+                .collect(),
+            heaviest,
+        }
+    }
+
+    pub fn render(&self, format: SizeFormat) -> String {
+        match format {
+            SizeFormat::Table => self.render_table(),
+            SizeFormat::Json => serde_json::to_string_pretty(self).unwrap() + "\n",
+        }
+    }
+
+    fn render_table(&self) -> String {
+        let share = |size: u64| match self.workspace.size {
+            0 => 0.0,
+            total => size as f64 * 100.0 / total as f64,
+        };
+        let mut rows = vec![[
+            "PROJECT".to_owned(),
+            "PACKAGES".to_owned(),
+            "SIZE".to_owned(),
+            "SHARE".to_owned(),
+        ]];
+        for (project, footprint) in self
+            .projects
+            .iter()
+            .chain([(&"(workspace)".to_owned(), &self.workspace)])
+        {
+            rows.push([
+                project.to_owned(),
+                footprint.packages.to_string(),
+                format_size(footprint.size),
+                format!("{:.1}%", share(footprint.size)),
+            ]);
+        }
+        let mut ret = render_rows(&rows);
+        if !self.heaviest.is_empty() {
+            let mut rows = vec![[
+                "PACKAGE".to_owned(),
+                "SIZE".to_owned(),
+                "PROJECTS".to_owned(),
+            ]];
+            for package in &self.heaviest {
+                rows.push([
+                    package.name.to_owned(),
+                    format_size(package.size),
+                    package
+                        .projects
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ]);
+            }
+            ret.push('\n');
+            ret.push_str(&render_rows(&rows));
+        }
+        ret
+    }
+}
+
+/// aligns the columns of `rows`, the last column is not padded
+fn render_rows<const N: usize>(rows: &[[String; N]]) -> String {
+    let widths = (0..N)
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+    rows.iter()
+        .map(|row| {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
+
+/// formats a number of bytes with the largest unit that keeps it above 1, eg: `1.5 MB`
+fn format_size(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} {}", UNITS[0]),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
@@ -0,0 +1,317 @@
+use super::{manifest::read_package_name, LibraryError};
+use std::{path::Path, str::FromStr};
+
+/// The prefixes of specifiers that point to a git repository, a tarball or a directory
+/// instead of a package of the registry
+const SOURCE_PREFIXES: [&str; 14] = [
+    "file:",
+    "link:",
+    "./",
+    "../",
+    "/",
+    "~/",
+    "git:",
+    "git+",
+    "http://",
+    "https://",
+    "github:",
+    "gitlab:",
+    "bitbucket:",
+    "gist:",
+];
+
+/// The prefix of a specifier that installs a package under a different name
+const ALIAS_PREFIX: &str = "npm:";
+
+/// A package as it is passed to `npm install`, eg: `react`, `react@^18.2.0`,
+/// `@types/node@latest`, `my-react@npm:react@18` or `lib@github:acme/lib#main`. Only the
+/// name is declared by projects, the complete specifier is passed to the package manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSpecifier {
+    /// the name the package is installed as, which is the key in `package.json`
+    pub name: String,
+    /// the specifier exactly as it was given
+    pub raw: String,
+}
+
+impl FromStr for PackageSpecifier {
+    type Err = LibraryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, range) = split_name_and_range(s);
+        // `lib@github:acme/lib` is named while `github:acme/lib` and
+        // `git@github.com:acme/lib.git` are not
+        let name = match is_valid_name(name)
+            && !(range.is_none() && is_tarball(s))
+            && !is_scp_url(s)
+        {
+            true => {
+                if let Some(aliased) = range.and_then(|r| r.strip_prefix(ALIAS_PREFIX)) {
+                    let (aliased, _) = split_name_and_range(aliased);
+                    if !is_valid_name(aliased) {
+                        return Err(LibraryError(format!(
+                            "{aliased:?} is not a valid package name in {s:?}"
+                        )));
+                    }
+                }
+                name.to_owned()
+            }
+            false if is_source(s) => get_local_package_name(s).ok_or_else(|| {
+                LibraryError(format!(
+                    "Could not find the name of the package in {s:?}, please pass it as `<name>@{s}`"
+                ))
+            })?,
+            false => {
+                return Err(LibraryError(format!(
+                    "{name:?} is not a valid package name in {s:?}"
+                )))
+            }
+        };
+        Ok(Self {
+            name,
+            raw: s.to_owned(),
+        })
+    }
+}
+
+/// whether the specifier is a git repository (including the `user/repo` shorthand of
+/// GitHub and scp-like URLs), a tarball or a directory without a name
+fn is_source(spec: &str) -> bool {
+    let is_github_shorthand = !spec.starts_with('@')
+        && spec
+            .find('/')
+            .is_some_and(|slash| !spec[..slash].contains('@'));
+    SOURCE_PREFIXES.iter().any(|p| spec.starts_with(p))
+        || is_github_shorthand
+        || is_scp_url(spec)
+        || is_tarball(spec)
+}
+
+/// whether the specifier is an scp-like git URL, eg: `git@github.com:acme/lib.git`, whose
+/// user would otherwise be read as the name of the package
+fn is_scp_url(spec: &str) -> bool {
+    let (user, location) = match spec.split_once('@') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let (host, path) = match location.split_once(':') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    !user.is_empty()
+        && !user.contains(['/', ':'])
+        && host.contains('.')
+        && !host.contains(['/', '@'])
+        && !path.is_empty()
+}
+
+fn is_tarball(spec: &str) -> bool {
+    [".tgz", ".tar.gz", ".tar"]
+        .iter()
+        .any(|e| spec.ends_with(e))
+}
+
+/// reads the name of a package from the `package.json` of a local directory
+fn get_local_package_name(spec: &str) -> Option<String> {
+    let path = spec
+        .strip_prefix("file:")
+        .or_else(|| spec.strip_prefix("link:"))
+        .unwrap_or(spec);
+    read_package_name(Path::new(path))
+}
+
+/// splits `name@range` into its parts, the leading `@` of a scoped name is not a
+/// separator
+pub(crate) fn split_name_and_range(spec: &str) -> (&str, Option<&str>) {
+    match spec[1.min(spec.len())..].find('@').map(|i| i + 1) {
+        Some(at) => (&spec[..at], Some(&spec[at + 1..])),
+        None => (spec, None),
+    }
+}
+
+/// whether `name` is a valid npm package name, optionally scoped. New packages can not
+/// have uppercase letters in their names, so they are rejected.
+fn is_valid_name(name: &str) -> bool {
+    let package = match name.strip_prefix('@') {
+        Some(scoped) => match scoped.split_once('/') {
+            Some((scope, package)) if is_valid_part(scope) => package,
+            _ => "",
+        },
+        None => name,
+    };
+    is_valid_part(package) && !package.starts_with(['.', '_'])
+}
+
+fn is_valid_part(part: &str) -> bool {
+    !part.is_empty()
+        && part.chars().all(|c| {
+            c.is_ascii_lowercase()
+                || c.is_ascii_digit()
+                || ['-', '.', '_', '~'].contains(&c)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+    use tempfile::tempdir;
+
+    fn name_of(spec: &str) -> String {
+        spec.parse::<PackageSpecifier>().unwrap().name
+    }
+
+    fn is_invalid(spec: &str) -> bool {
+        spec.parse::<PackageSpecifier>().is_err()
+    }
+
+    #[test]
+    fn parses_names_and_ranges() {
+        assert_eq!(name_of("react"), "react");
+        assert_eq!(name_of("react@^18.2.0"), "react");
+        assert_eq!(name_of("react@>=16 <19"), "react");
+        assert_eq!(name_of("lodash.merge@4.6.2"), "lodash.merge");
+        let specifier = "react@18".parse::<PackageSpecifier>().unwrap();
+        assert_eq!(specifier.raw, "react@18");
+    }
+
+    #[test]
+    fn parses_dist_tags() {
+        assert_eq!(name_of("typescript@next"), "typescript");
+        assert_eq!(name_of("@types/node@latest"), "@types/node");
+    }
+
+    #[test]
+    fn parses_scoped_names() {
+        assert_eq!(name_of("@types/node"), "@types/node");
+        assert_eq!(name_of("@nrwl/next@14.0.0"), "@nrwl/next");
+        assert!(is_invalid("@types"));
+        assert!(is_invalid("@/node"));
+        assert!(is_invalid("@types/"));
+    }
+
+    #[test]
+    fn parses_aliases() {
+        assert_eq!(name_of("my-react@npm:react@18"), "my-react");
+        assert_eq!(name_of("types@npm:@types/node"), "types");
+        assert!(is_invalid("my-react@npm:Not Valid"));
+        assert!(is_invalid("my-react@npm:@scope"));
+    }
+
+    #[test]
+    fn parses_named_sources() {
+        assert_eq!(name_of("lib@github:acme/lib#main"), "lib");
+        assert_eq!(name_of("lib@git+https://github.com/acme/lib.git"), "lib");
+        assert_eq!(name_of("lib@https://example.com/lib-1.0.0.tgz"), "lib");
+        assert_eq!(name_of("lib@file:../lib"), "lib");
+    }
+
+    #[test]
+    fn rejects_unnamed_sources_without_a_package_json() {
+        for spec in [
+            "github:acme/lib",
+            "acme/lib",
+            "git+https://github.com/acme/lib.git",
+            "https://example.com/lib-1.0.0.tgz",
+            "lib-1.0.0.tgz",
+            "file:./does-not-exist",
+        ] {
+            assert!(is_source(spec), "{spec:?} is a source");
+            let err = spec.parse::<PackageSpecifier>().unwrap_err();
+            assert!(
+                err.0.contains("Could not find the name"),
+                "{spec:?}: {}",
+                err.0
+            );
+        }
+    }
+
+    #[test]
+    fn reads_the_name_of_local_directories() {
+        let directory = tempdir().unwrap();
+        write(
+            directory.path().join("package.json"),
+            r#"{"name": "@acme/local", "version": "1.0.0"}"#,
+        )
+        .unwrap();
+        let path = directory.path().display();
+        assert_eq!(name_of(&format!("file:{path}")), "@acme/local");
+        assert_eq!(name_of(&format!("link:{path}")), "@acme/local");
+        assert_eq!(name_of(&path.to_string()), "@acme/local");
+    }
+
+    #[test]
+    fn rejects_invalid_names() {
+        for spec in ["", "Not Valid", "_private", ".hidden", "react!@1", "@"] {
+            assert!(is_invalid(spec), "{spec:?} is invalid");
+        }
+    }
+
+    #[test]
+    fn rejects_uppercase_names() {
+        for spec in [
+            "React",
+            "JSONStream@1.3.5",
+            "@Types/node",
+            "@types/Node",
+            "a@npm:React",
+        ] {
+            assert!(is_invalid(spec), "{spec:?} is invalid");
+        }
+        assert_eq!(name_of("react-dom2@npm:react-dom@18"), "react-dom2");
+    }
+
+    #[test]
+    fn parses_scp_like_git_urls() {
+        for spec in [
+            "git@github.com:acme/lib.git",
+            "git@github.com:acme/lib.git#main",
+            "deploy@git.example.com:libs/lib",
+        ] {
+            assert!(is_scp_url(spec), "{spec:?} is an scp-like URL");
+            assert!(is_source(spec), "{spec:?} is a source");
+            let err = spec.parse::<PackageSpecifier>().unwrap_err();
+            assert!(
+                err.0.contains("Could not find the name"),
+                "{spec:?}: {}",
+                err.0
+            );
+        }
+        assert_eq!(name_of("lib@git@github.com:acme/lib.git"), "lib");
+        assert_eq!(name_of("lib@git+ssh://git@github.com:acme/lib.git"), "lib");
+        for spec in [
+            "lib@github:acme/lib",
+            "react@18",
+            "@types/node@latest",
+            "a@npm:b@1",
+        ] {
+            assert!(!is_scp_url(spec), "{spec:?} is not an scp-like URL");
+        }
+    }
+
+    #[test]
+    fn detects_sources() {
+        for spec in [
+            "./lib",
+            "../lib",
+            "/lib",
+            "~/lib",
+            "link:../lib",
+            "gist:123",
+        ] {
+            assert!(is_source(spec), "{spec:?} is a source");
+        }
+        for spec in ["react", "react@18", "@types/node", "@types/node@18"] {
+            assert!(!is_source(spec), "{spec:?} is not a source");
+        }
+    }
+
+    #[test]
+    fn splits_names_and_ranges() {
+        assert_eq!(split_name_and_range("react"), ("react", None));
+        assert_eq!(split_name_and_range("react@18"), ("react", Some("18")));
+        assert_eq!(split_name_and_range("@a/b@1"), ("@a/b", Some("1")));
+        assert_eq!(split_name_and_range("@a/b"), ("@a/b", None));
+        assert_eq!(split_name_and_range(""), ("", None));
+    }
+}
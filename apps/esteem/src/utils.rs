@@ -1,11 +1,18 @@
 use super::{
-    constants::WORKSPACE_FILE, graph::NxProject, managers::PackageManager,
-    project::EsteemProject, workspace::EsteemWorkspace,
+    cache::GraphCache,
+    config::{EsteemConfig, GraphProvider},
+    constants::{GRAPH_FILE, WORKSPACE_FILE},
+    graph::{Direction, NxProject},
+    managers::PackageManager,
+    project::EsteemProject,
+    workspace::EsteemWorkspace,
+    LibraryError,
 };
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     path::PathBuf,
 };
+use tempfile::tempdir;
 
 pub fn display_warning(key: &str, dependency: &str, path: &PathBuf) {
     warn!("{:?} not found in {:?} of {:?}", dependency, key, path);
@@ -27,27 +34,166 @@ pub fn get_all_project_names() -> Vec<String> {
     projects.keys().cloned().collect()
 }
 
+/// calls NX to calculate the project graph, focused on `project_name` if it is given, or
+/// reads it from the file set in the configuration
+fn calculate_project_graph(
+    project_name: Option<&String>,
+    call_script_executor: bool,
+) -> Result<NxProject, LibraryError> {
+    let config = EsteemConfig::get();
+    if config.graph.provider == GraphProvider::File {
+        let path = config.graph.path.as_ref().unwrap();
+        info!("Reading the project graph from {path:?}");
+        return NxProject::from_path(path);
+    }
+    // the directory (and the graph written to it) is removed when it goes out of scope
+    let directory = tempdir()
+        .map_err(|err| LibraryError(format!("Could not create directory: {err}")))?;
+    let path = directory.path().join(GRAPH_FILE);
+    let mut manager = PackageManager::get_command_executor(call_script_executor)?;
+    manager.graph_dependencies(project_name, &path)?;
+    let output = manager.execute_script_captured().map_err(|err| {
+        LibraryError(format!("Could not calculate the project graph: {}", err.0))
+    })?;
+    debug!("Output of NX: {output}");
+    NxProject::from_path(&path).map_err(|err| {
+        LibraryError(format!(
+            "NX did not produce a project graph: {}\nOutput of NX: {output}",
+            err.0
+        ))
+    })
+}
+
+/// returns the project graph (focused on `project_name` if it is given). When the cache
+/// is used, the complete graph is returned instead since it is valid for all projects.
+/// Graphs read from a file are never cached.
+pub fn get_project_graph(
+    workspace: &EsteemWorkspace,
+    project_name: Option<&String>,
+    call_script_executor: bool,
+    use_cache: bool,
+) -> Result<NxProject, LibraryError> {
+    if !use_cache || EsteemConfig::get().graph.provider == GraphProvider::File {
+        return calculate_project_graph(project_name, call_script_executor);
+    }
+    let cache = GraphCache::new(workspace);
+    if let Some(graph) = cache.read() {
+        return Ok(graph);
+    }
+    let graph = calculate_project_graph(None, call_script_executor)?;
+    if let Err(err) = cache.write(&graph) {
+        warn!("{}", err.0);
+    }
+    Ok(graph)
+}
+
+/// returns all projects connected to any of `project_names` in the given direction. The
+/// project graph is calculated only once, no matter how many projects are given.
 pub fn get_project_dependencies(
-    project_name: &String,
+    workspace: &EsteemWorkspace,
+    project_names: &[String],
     call_script_executor: bool,
-) -> Vec<EsteemProject> {
-    let mut manager = PackageManager::get_command_executor(call_script_executor).unwrap();
-    let path = manager.graph_dependencies(project_name);
-    manager.execute_script();
-    let project = NxProject::from_path(path).unwrap();
-    let projects_names = project.get_project_dependencies();
-    let workspace = EsteemWorkspace::from_current_directory().unwrap();
-    let projects = projects_names
+    direction: Direction,
+    use_cache: bool,
+) -> Result<Vec<EsteemProject>, LibraryError> {
+    // a focused graph only contains the projects connected to a single project
+    let focus = match project_names {
+        [project_name] => Some(project_name),
+        _ => None,
+    };
+    let project = get_project_graph(workspace, focus, call_script_executor, use_cache)?;
+    let projects_names = project.get_projects_dependencies(project_names, direction)?;
+    projects_names
         .iter()
-        .map(|p| workspace.get_project(p.to_string()).cloned().unwrap())
-        .collect();
-    projects
+        .map(|p| workspace.get_project(p.to_string()).cloned())
+        .collect()
 }
 
-pub fn get_projects_with_config_path(project_name: &String) -> HashMap<String, PathBuf> {
-    let mut manager = PackageManager::get_command_executor(true).unwrap();
-    let path = manager.graph_dependencies(project_name);
-    manager.execute_script();
-    let project = NxProject::from_path(path).unwrap();
-    project.get_projects_with_config_path()
+/// returns the projects needed to isolate `project_names`: their upstream projects along
+/// with the projects of the internal packages that any of them declare (and the upstream
+/// projects of those). When `without_graph` is set, NX is not called and only the
+/// internal packages are followed.
+pub fn get_isolated_projects(
+    workspace: &EsteemWorkspace,
+    project_names: &[String],
+    call_script_executor: bool,
+    use_cache: bool,
+    without_graph: bool,
+) -> Result<BTreeSet<String>, LibraryError> {
+    let graph = match without_graph {
+        true => None,
+        false => Some(get_isolation_graph(
+            workspace,
+            project_names,
+            call_script_executor,
+            use_cache,
+        )?),
+    };
+    isolate_projects(workspace, project_names, graph.as_ref())
+}
+
+/// returns the project graph needed to isolate `project_names`, which is focused when a
+/// single project is given and the workspace has no internal packages. The graph can be
+/// reused to isolate each of the projects with [`isolate_projects`].
+pub(crate) fn get_isolation_graph(
+    workspace: &EsteemWorkspace,
+    project_names: &[String],
+    call_script_executor: bool,
+    use_cache: bool,
+) -> Result<NxProject, LibraryError> {
+    // a focused graph might not contain the projects of internal packages
+    let focus = match project_names {
+        [project_name] if workspace.get_internal_packages().is_empty() => {
+            Some(project_name)
+        }
+        _ => None,
+    };
+    get_project_graph(workspace, focus, call_script_executor, use_cache)
+}
+
+/// returns the projects needed to isolate `project_names` like [`get_isolated_projects`],
+/// with a graph that is already calculated. Only the internal packages are followed if
+/// there is no graph.
+pub(crate) fn isolate_projects(
+    workspace: &EsteemWorkspace,
+    project_names: &[String],
+    graph: Option<&NxProject>,
+) -> Result<BTreeSet<String>, LibraryError> {
+    let internal = workspace.get_internal_packages();
+    let mut names = project_names.to_vec();
+    loop {
+        let projects = match graph {
+            Some(graph) => {
+                graph.get_projects_dependencies(&names, Direction::Upstream)?
+            }
+            None => names.iter().cloned().collect(),
+        };
+        let found = projects
+            .iter()
+            .filter_map(|p| workspace.get_project(p.to_owned()).ok())
+            .flat_map(|p| workspace.resolve(&p.dependencies).get_all_dependencies())
+            .chain(
+                workspace
+                    .resolve(&workspace.dependencies)
+                    .get_all_dependencies(),
+            )
+            .filter_map(|package| internal.get(&package))
+            .map(|package| package.project.to_owned())
+            .filter(|project| !projects.contains(project))
+            .collect::<BTreeSet<_>>();
+        if found.is_empty() {
+            return Ok(projects);
+        }
+        info!("Including the projects of internal packages: {found:?}");
+        names = projects.into_iter().chain(found).collect();
+    }
+}
+
+pub fn get_projects_with_config_path(
+    project_name: &String,
+    use_cache: bool,
+) -> Result<HashMap<String, PathBuf>, LibraryError> {
+    let workspace = EsteemWorkspace::from_current_directory()?;
+    let project = get_project_graph(&workspace, Some(project_name), true, use_cache)?;
+    project.get_projects_with_config_path(project_name, Direction::Upstream)
 }
@@ -1,9 +1,13 @@
 use super::{
     constants::{workspace_file, WORKSPACE_FILE},
     dependencies::EsteemDependencies,
+    manifest::read_package_name,
     project::EsteemProject,
-    AddEsteemDevelopmentDependency, AddEsteemRequiredDependency, LibraryError,
-    RemoveEsteemDevelopmentDependency, RemoveEsteemRequiredDependency, WriteDependencies,
+    AddEsteemDevelopmentDependency, AddEsteemOptionalDependency, AddEsteemPeerDependency,
+    AddEsteemRequiredDependency, AddEsteemTargetDependency, LibraryError,
+    RemoveEsteemDevelopmentDependency, RemoveEsteemOptionalDependency,
+    RemoveEsteemPeerDependency, RemoveEsteemRequiredDependency,
+    RemoveEsteemTargetDependency, WriteDependencies,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -12,8 +16,71 @@ use std::{
     env::current_dir,
     fs::read_to_string,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
+/// A project that is also a package of the workspace, eg: referenced as
+/// `"@acme/ui": "workspace:*"` in `package.json`
+#[derive(Debug, Clone)]
+pub struct InternalPackage {
+    /// the name of the project
+    pub project: String,
+    /// the root of the project, relative to the workspace
+    pub root: PathBuf,
+}
+
+/// What `install-isolated` writes for the internal packages that are kept
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkspaceProtocol {
+    /// write the version of the root `package.json` (eg: `workspace:*`) as it is
+    #[default]
+    Keep,
+    /// point to the root of the project, eg: `file:libs/ui`, which every package
+    /// manager understands
+    File,
+    /// leave out internal packages, eg: when their sources are bundled
+    Strip,
+}
+
+impl WorkspaceProtocol {
+    pub const VARIANTS: [&'static str; 3] = ["keep", "file", "strip"];
+
+    /// applies this mode to the `versions` that are written to `package.json`
+    pub(crate) fn apply(
+        &self,
+        versions: &mut BTreeMap<String, String>,
+        internal: &BTreeMap<String, InternalPackage>,
+    ) {
+        match self {
+            Self::Keep => {}
+            Self::File => {
+                for (package, version) in versions.iter_mut() {
+                    if let Some(internal) = internal.get(package) {
+                        *version = format!("file:{}", internal.root.display());
+                    }
+                }
+            }
+            Self::Strip => versions.retain(|package, _| !internal.contains_key(package)),
+        }
+    }
+}
+
+impl FromStr for WorkspaceProtocol {
+    type Err = LibraryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(Self::Keep),
+            "file" => Ok(Self::File),
+            "strip" => Ok(Self::Strip),
+            _ => Err(LibraryError(format!(
+                "Unknown workspace protocol mode: {s:?}"
+            ))),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EsteemWorkspace {
     /// The complete path to this project
@@ -30,6 +97,10 @@ pub struct EsteemWorkspace {
     #[serde(default)]
     pub(crate) dependencies: EsteemDependencies,
 
+    /// named groups of dependencies that projects can include by referencing them
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) groups: BTreeMap<String, EsteemDependencies>,
+
     /// the other miscellaneous keys that we do not care about
     #[serde(flatten)]
     other: BTreeMap<String, Value>,
@@ -91,14 +162,48 @@ impl EsteemWorkspace {
         })
     }
 
+    /// returns the packages of the workspace (projects with a `package.json` at their
+    /// root), keyed by the name they are published as
+    pub(crate) fn get_internal_packages(&self) -> BTreeMap<String, InternalPackage> {
+        self.projects
+            .iter()
+            .filter_map(|(project, root)| {
+                let name = read_package_name(root)?;
+                trace!("{project:?} is the internal package {name:?}");
+                let package = InternalPackage {
+                    project: project.to_owned(),
+                    root: root.to_owned(),
+                };
+                Some((name, package))
+            })
+            .collect()
+    }
+
+    /// returns `dependencies` with the groups they reference replaced by the dependencies
+    /// of those groups. Groups can not reference other groups.
+    pub(crate) fn resolve(
+        &self,
+        dependencies: &EsteemDependencies,
+    ) -> EsteemDependencies {
+        let mut resolved = dependencies.clone();
+        resolved.groups.clear();
+        for name in &dependencies.groups {
+            match self.groups.get(name) {
+                Some(group) => resolved.extend(group),
+                None => warn!("{name:?} is not a dependency group of {WORKSPACE_FILE:?}"),
+            }
+        }
+        resolved
+    }
+
     /// returns all dependencies of this project (project and workspace scoped)
     fn get_all_dependencies(&self) -> HashSet<String> {
-        let workspace_deps = self.dependencies.get_all_dependencies();
+        let workspace_deps = self.resolve(&self.dependencies).get_all_dependencies();
         let projects_deps = self
             .all_projects_rep
             .iter()
-            .flat_map(|p| p.dependencies.get_all_dependencies());
-        let all_deps_vec = workspace_deps.into_iter().chain(projects_deps.into_iter());
+            .flat_map(|p| self.resolve(&p.dependencies).get_all_dependencies());
+        let all_deps_vec = workspace_deps.into_iter().chain(projects_deps);
         HashSet::from_iter(all_deps_vec)
     }
 
@@ -138,6 +243,18 @@ impl AddEsteemDevelopmentDependency for EsteemWorkspace {
     }
 }
 
+impl AddEsteemPeerDependency for EsteemWorkspace {
+    fn add_peer_dependency(&mut self, dependency: String) {
+        self.dependencies.add_peer_dependency(dependency);
+    }
+}
+
+impl AddEsteemOptionalDependency for EsteemWorkspace {
+    fn add_optional_dependency(&mut self, dependency: String) {
+        self.dependencies.add_optional_dependency(dependency);
+    }
+}
+
 impl WriteDependencies for EsteemWorkspace {
     fn get_path(&self) -> PathBuf {
         self.path.clone()
@@ -161,3 +278,33 @@ impl RemoveEsteemDevelopmentDependency for EsteemWorkspace {
         self.dependencies.remove_development_dependency(dependency)
     }
 }
+
+impl RemoveEsteemPeerDependency for EsteemWorkspace {
+    fn remove_peer_dependency(&mut self, dependency: String) -> Result<(), LibraryError> {
+        self.dependencies.remove_peer_dependency(dependency)
+    }
+}
+
+impl RemoveEsteemOptionalDependency for EsteemWorkspace {
+    fn remove_optional_dependency(
+        &mut self,
+        dependency: String,
+    ) -> Result<(), LibraryError> {
+        self.dependencies.remove_optional_dependency(dependency)
+    }
+}
+
+impl AddEsteemTargetDependency for EsteemWorkspace {
+    fn add_target_dependency(&mut self, target: String, dependency: String) {
+        self.dependencies.add_target_dependency(target, dependency);
+    }
+}
+
+impl RemoveEsteemTargetDependency for EsteemWorkspace {
+    fn remove_target_dependency(
+        &mut self,
+        dependency: String,
+    ) -> Result<(), LibraryError> {
+        self.dependencies.remove_target_dependency(dependency)
+    }
+}